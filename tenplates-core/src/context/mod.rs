@@ -2,33 +2,118 @@
 mod test;
 
 use {
+    crate::{
+        error::{ InternalError, InternalResult, IntoInternal, },
+        filter::Escape,
+        value::{ IsTruthy, List, Map, Row, Rows, ToOutput, Value, },
+    },
+    rusqlite::Statement,
     std::{
-        collections::HashMap,
+        collections::{ BTreeSet, HashMap, },
         fmt::Debug,
+        str::FromStr,
     },
     std::path::{ Path, PathBuf, },
 };
 
+/// A parsed reference to a variable in context, optionally narrowed to a
+/// specific row and/or column of the stored value.
+///
+/// `name`, `name[0]`, `name[column]` and `name[0][column]` are all valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    variable: String,
+    row: Option<usize>,
+    column: Option<String>,
+}
+
+impl FromStr for Alias {
+    type Err = InternalError;
+
+    fn from_str(input: &str) -> InternalResult<Self> {
+        let (variable, mut rest) = match input.find('[') {
+            Some(idx) => (&input[..idx], &input[idx..]),
+            None => (input, ""),
+        };
+
+        if variable.is_empty() {
+            return Err(InternalError::new(format!("Alias '{input}' is missing a variable name")));
+        }
+
+        let mut row = None;
+        let mut column = None;
+        while !rest.is_empty() {
+            let end = rest.find(']')
+                .into_internal(format!("Alias '{input}' is missing a closing ']'"))?;
+            let index = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(InternalError::new(format!("Unexpected characters after ']' in alias '{input}'")));
+            }
+
+            if index.is_empty() {
+                return Err(InternalError::new(format!("Empty index in alias '{input}'")));
+            }
+            else if column.is_some() {
+                return Err(InternalError::new(format!("Alias '{input}' cannot index past a column")));
+            }
+            else if let Ok(index) = index.parse::<usize>() {
+                if row.is_some() {
+                    return Err(InternalError::new(format!("Alias '{input}' cannot index more than one row")));
+                }
+
+                row = Some(index);
+            }
+            else {
+                column = Some(index.to_owned());
+            }
+        }
+
+        Ok(Self { variable: variable.to_owned(), row, column, })
+    }
+}
+
+impl Alias {
+    pub(crate) fn has_row(&self) -> bool {
+        self.row.is_some()
+    }
+
+    pub(crate) fn has_column(&self) -> bool {
+        self.column.is_some()
+    }
+
+    pub(crate) fn into_variable(self) -> String {
+        self.variable
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Variable {
     path: PathBuf,
-    value: String,
+    value: Value,
+    trusted: bool,
+    // when the value was added, a value added to an object masks the older
+    // variables beneath it
+    order: usize,
 }
 
 impl Variable {
-    fn new<P: AsRef<Path>, V: AsRef<str>>(path: P, value: V) -> Self {
-        Self { path: path.as_ref().into(), value: value.as_ref().to_owned(), }
+    fn new<P: AsRef<Path>, V: Into<Value>>(path: P, value: V, order: usize) -> Self {
+        Self { path: path.as_ref().into(), value: value.into(), trusted: false, order, }
     }
 
-    pub(crate) fn value(&self) -> &String {
+    pub(crate) fn value(&self) -> &Value {
         &self.value
     }
 
-    pub(crate) fn value_as_path(&self) -> PathBuf {
-        let path = PathBuf::from(self.value());
+    /// Resolves `value`, i.e. a member of this variable's value, against the
+    /// path the variable was set in.
+    fn resolve_path(&self, value: &Value) -> InternalResult<PathBuf> {
+        let path = PathBuf::from(value.to_output(&[])?);
 
         if path.is_absolute() {
-            return path;
+            return Ok(path);
         }
 
         let mut base = self.path.clone();
@@ -36,38 +121,149 @@ impl Variable {
 
         base.push(path);
 
-        base
+        Ok(base)
+    }
+
+    pub(crate) fn value_is_truthy(value: Option<&Value>) -> bool {
+        value.is_some_and(IsTruthy::is_truthy)
+    }
+}
+
+// members are inserted in sorted order, so a value is always inserted before
+// the variables beneath it, which then override its members in the same way as
+// `Context::value`
+fn insert_member(map: &mut Map, path: &str, value: Value) {
+    match path.split_once('.') {
+        Some((key, rest)) => {
+            if map.get(key).is_none() {
+                map.insert(key, Map::default());
+            }
+
+            // a member which is not a map masks the variables beneath it
+            if let Some(Value::Map(member)) = map.get_mut(key) {
+                insert_member(member, rest, value);
+            }
+        },
+        None => {
+            map.insert(path, value);
+        },
     }
+}
+
+// each object a variable is beneath, i.e. `a` and `a.b` for `a.b.c`
+fn objects(key: &str) -> impl Iterator<Item = &str> {
+    key.match_indices('.').map(|(idx, _)| &key[..idx])
+}
 
-    pub(crate) fn value_is_truthy<S: AsRef<str>>(value: Option<S>) -> bool {
-        !value.is_none_or(|v| matches!(v.as_ref().to_lowercase().as_str(), "n"|"0"|""|"false"))
+/// A parameter of a [Function], i.e. `title`, `class="default"` or `*rest`
+/// in `{% fn card(title, class="default", *rest) %}`.
+#[derive(Debug, Clone)]
+pub(crate) enum Param {
+    Required(String),
+    Default(String, Value),
+    /// Collects the remaining positional arguments into a list.
+    Variadic(String),
+}
+
+impl Param {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Self::Required(name)|Self::Default(name, _)|Self::Variadic(name) => name,
+        }
     }
 }
 
+/// An argument passed to a [Function], named when passed by keyword, i.e.
+/// `title="X"` in `{{ card(title="X") }}`.
+#[derive(Debug, Clone)]
+pub(crate) struct Arg {
+    pub(crate) name: Option<String>,
+    pub(crate) value: Option<Value>,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Function {
-    args: Vec<String>,
+    params: Vec<Param>,
     content: String,
 }
 
 impl Function {
-    fn new(args: Vec<String>, content: String) -> Self {
-        Self { args, content, }
+    fn new(params: Vec<Param>, content: String) -> Self {
+        Self { params, content, }
     }
 
     pub(crate) fn as_bytes(&self) -> &[u8] {
         self.content.as_bytes()
     }
 
-    pub(crate) fn args(&self) -> &Vec<String> {
-        &self.args
+    /// Binds the arguments of a call to the function `name` to its
+    /// parameters, returning the value of each parameter. A parameter which
+    /// was not passed, or was passed an undefined value, takes its default or
+    /// is otherwise [None].
+    pub(crate) fn bind(&self, name: &str, args: Vec<Arg>) -> InternalResult<Vec<(String, Option<Value>)>> {
+        let positional = self.params.iter()
+            .filter(|param| !matches!(param, Param::Variadic(_)))
+            .count();
+        let variadic = self.params.iter().any(|param| matches!(param, Param::Variadic(_)));
+
+        let mut bound = vec![None; positional];
+        let mut passed = vec![false; positional];
+        let mut rest = Vec::new();
+
+        let received = args.len();
+        for (index, arg) in args.into_iter().enumerate() {
+            match arg.name {
+                Some(key) => {
+                    let position = self.params.iter()
+                        .take(positional)
+                        .position(|param| param.name() == key)
+                        .into_internal(format!("Function '{name}' has no argument '{key}'"))?;
+
+                    if passed[position] {
+                        return Err(InternalError::new(format!(
+                            "Argument '{key}' of function '{name}' was passed more than once"
+                        )));
+                    }
+
+                    bound[position] = arg.value;
+                    passed[position] = true;
+                },
+                None if index < positional => {
+                    bound[index] = arg.value;
+                    passed[index] = true;
+                },
+                None if variadic => rest.push(arg.value.unwrap_or_default()),
+                None => return Err(InternalError::new(format!(
+                    "Function '{name}' expects at most {positional} argument(s) but received {received}"
+                ))),
+            }
+        }
+
+        let mut bound = bound.into_iter().zip(passed);
+        let values = self.params.iter()
+            .map(|param| (param.name().to_owned(), match param {
+                Param::Required(_) => bound.next().and_then(|(value, _)| value),
+                Param::Default(_, default) => bound.next()
+                    .and_then(|(value, _)| value)
+                    .or_else(|| Some(default.to_owned())),
+                Param::Variadic(_) => Some(Value::from(List::from(std::mem::take(&mut rest)))),
+            }))
+            .collect();
+
+        Ok(values)
     }
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Context {
     variables: HashMap<String, Vec<Variable>>,
+    /// The names of the variables beneath each object, i.e. `a.b` and `a.b.c`
+    /// beneath `a`.
+    members: HashMap<String, BTreeSet<String>>,
     functions: HashMap<String, Vec<Function>>,
+    escape: Option<Escape>,
+    // the number of values ever added, used to order them
+    order: usize,
 }
 
 impl Context {
@@ -79,33 +275,218 @@ impl Context {
         self.variables.get_mut(key.as_ref())
     }
 
-    pub(crate) fn value<K: AsRef<str>>(&self, key: K) -> Option<&String> {
-        self.variables(key)?.last().map(|l| l.value())
+    fn variable<K: AsRef<str>>(&self, key: K) -> Option<&Variable> {
+        self.variables(key).and_then(|variables| variables.last())
+    }
+
+    /// The variable which `key` is resolved from along with the resolved
+    /// value. `a.b.c` is the variable itself, the member `c` of a value
+    /// stored in `a.b` or the member `b.c` of a value stored in `a`, whichever
+    /// was added last.
+    fn resolve(&self, key: &str) -> Option<(&Variable, Option<&Value>)> {
+        let mut resolved = self.variable(key).map(|variable| (variable, Some(variable.value())));
+
+        for object in objects(key) {
+            let Some(variable) = self.variable(object) else {
+                continue;
+            };
+
+            if resolved.is_none_or(|(newest, _)| variable.order > newest.order) {
+                let value = key[object.len() + 1..].split('.')
+                    .try_fold(variable.value(), |value, member| value.get(member));
+
+                resolved = Some((variable, value));
+            }
+        }
+
+        resolved
+    }
+
+    /// Whether a value was added to an object above the variable `key` after
+    /// the variable itself.
+    fn is_masked(&self, key: &str) -> bool {
+        self.variable(key).is_some_and(|variable| objects(key)
+            .filter_map(|object| self.variable(object))
+            .any(|object| object.order > variable.order))
     }
 
-    pub(crate) fn path<K: AsRef<str>>(&self, key: K) -> Option<PathBuf> {
-        let variables = self.variables(key)?;
-        Some(variables.last().unwrap().value_as_path())
+    pub(crate) fn value<K: AsRef<str>>(&self, key: K) -> Option<&Value> {
+        self.resolve(key.as_ref()).and_then(|(_, value)| value)
     }
 
-    pub(crate) fn values<K: AsRef<str>>(&self, key: K) -> Option<Vec<&String>> {
-        self.variables(key).map(|variables| variables.iter().map(|s| s.value()).collect::<Vec<&String>>())
+    /// The value of a variable merged with the variables beneath it, i.e.
+    /// `person.name` and `person.age` for `person`. When the variable was
+    /// never set, the value is a [map](Map) of the variables beneath it.
+    pub(crate) fn object<K: AsRef<str>>(&self, key: K) -> Option<Value> {
+        let key = key.as_ref();
+        let value = self.value(key);
+
+        let members = self.members(key);
+        if members.is_empty() {
+            return value.cloned();
+        }
+
+        let mut map = match value {
+            Some(Value::Map(map)) => map.to_owned(),
+            Some(value) => return Some(value.to_owned()),
+            None => Map::default(),
+        };
+
+        for (name, value) in members {
+            insert_member(&mut map, &name[key.len() + 1..], value);
+        }
+
+        Some(Value::from(map))
+    }
+
+    /// The current value of each variable beneath the object `key`, in order
+    /// of their names.
+    pub(crate) fn members(&self, key: &str) -> Vec<(String, Value)> {
+        self.members.get(key)
+            .into_iter()
+            .flatten()
+            .filter(|name| !self.is_masked(name))
+            .filter_map(|name| self.variable(name)
+                .map(|variable| (name.to_owned(), variable.value().to_owned())))
+            .collect()
+    }
+
+    pub(crate) fn path<K: AsRef<str>>(&self, key: K) -> InternalResult<Option<PathBuf>> {
+        match self.resolve(key.as_ref()) {
+            Some((variable, Some(value))) => Ok(Some(variable.resolve_path(value)?)),
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) fn values<K: AsRef<str>>(&self, key: K) -> Option<Vec<&Value>> {
+        match self.variables(key.as_ref()) {
+            Some(variables) if !self.is_masked(key.as_ref()) => {
+                Some(variables.iter().map(|s| s.value()).collect::<Vec<&Value>>())
+            },
+            _ => self.value(key).map(|value| vec![ value ]),
+        }
     }
 
-    pub fn add_variable<K: AsRef<str>, P: AsRef<Path>, V: AsRef<str>>(&mut self, key: K, path: P, value: V) {
+    /// Add a value to a variable in context. Previous values of the variable
+    /// are masked, not overwritten.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the variable.
+    /// * `path` - The path that relative path values are resolved against.
+    /// * `value` - Anything which converts into a [value](Value).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tenplates_core::{ Context, Value, };
+    ///
+    /// let mut ctx = Context::default();
+    /// ctx.add_variable("title", "./", "Hello");
+    /// ctx.add_variable("count", "./", Value::from(5_i64));
+    /// ```
+    ///
+    pub fn add_variable<K: AsRef<str>, P: AsRef<Path>, V: Into<Value>>(&mut self, key: K, path: P, value: V) {
         if self.variables_mut(key.as_ref()).is_none() {
             self.variables.insert(key.as_ref().to_owned(), Vec::new());
+
+            for object in objects(key.as_ref()) {
+                self.members.entry(object.to_owned())
+                    .or_default()
+                    .insert(key.as_ref().to_owned());
+            }
         }
 
-        self.variables_mut(key.as_ref()).unwrap().push(Variable::new(path, value));
+        self.order += 1;
+        let variable = Variable::new(path, value, self.order);
+        self.variables_mut(key.as_ref()).unwrap().push(variable);
+    }
+
+    /// Adds a variable whose value is already compiled output, such as
+    /// `CONTENT`, so that it is never escaped again.
+    pub(crate) fn add_trusted_variable<K: AsRef<str>, P: AsRef<Path>, V: Into<Value>>(&mut self, key: K, path: P, value: V) {
+        self.add_variable(key.as_ref(), path, value);
+        self.variables_mut(key.as_ref()).unwrap().last_mut().unwrap().trusted = true;
+    }
+
+    pub(crate) fn is_trusted<K: AsRef<str>>(&self, key: K) -> bool {
+        self.variables(key).and_then(|v| v.last()).is_some_and(|v| v.trusted)
+    }
+
+    /// Add a value to the variable named by an [alias](Alias). The alias
+    /// cannot index a row or column of the variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The alias of the variable.
+    /// * `value` - Anything which converts into a [value](Value).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tenplates_core::{ Alias, Context, };
+    ///
+    /// let mut ctx = Context::default();
+    /// ctx.insert("title".parse::<Alias>().unwrap(), "Hello").unwrap();
+    /// assert!(ctx.insert("rows[0]".parse::<Alias>().unwrap(), "Hello").is_err());
+    /// ```
+    ///
+    pub fn insert<V: Into<Value>>(&mut self, alias: Alias, value: V) -> InternalResult<()> {
+        if alias.has_row() || alias.has_column() {
+            return Err(InternalError::new("Cannot insert a value into a specific row or column of a variable"));
+        }
+
+        self.add_variable(alias.into_variable(), PathBuf::new(), value);
+
+        Ok(())
     }
 
     pub(crate) fn remove_variable<K: AsRef<str>>(&mut self, key: K) -> Option<Vec<Variable>> {
-        if self.variables(key.as_ref()).is_some() {
-            return self.variables.remove(key.as_ref());
+        let removed = self.variables.remove(key.as_ref())?;
+
+        for object in objects(key.as_ref()) {
+            if let Some(members) = self.members.get_mut(object) {
+                members.remove(key.as_ref());
+
+                if members.is_empty() {
+                    self.members.remove(object);
+                }
+            }
         }
 
-        None
+        Some(removed)
+    }
+
+    /// Removes every stacked value of the variable `key` along with the
+    /// members of the object it names, i.e. `key.name`.
+    pub(crate) fn unset_variable<K: AsRef<str>>(&mut self, key: K) {
+        let members = self.members.get(key.as_ref()).cloned().unwrap_or_default();
+
+        for name in members {
+            self.remove_variable(name);
+        }
+
+        self.remove_variable(key);
+    }
+
+    /// The number of values stacked in the variable `key`.
+    pub(crate) fn depth<K: AsRef<str>>(&self, key: K) -> usize {
+        self.variables(key).map_or(0, Vec::len)
+    }
+
+    /// Removes each value stacked in the variable `key` above `depth`.
+    pub(crate) fn truncate_variable<K: AsRef<str>>(&mut self, key: K, depth: usize) {
+        let is_empty = match self.variables_mut(key.as_ref()) {
+            Some(variables) => {
+                variables.truncate(depth);
+                variables.is_empty()
+            },
+            None => false,
+        };
+
+        if is_empty {
+            self.remove_variable(key);
+        }
     }
 
     pub(crate) fn pop_variable<K: AsRef<str>>(&mut self, key: K) -> Option<Variable> {
@@ -123,15 +504,87 @@ impl Context {
         None
     }
 
+    /// Executes a prepared statement, binding each named parameter (`:name`,
+    /// `@name` or `$name`) to the current value of the variable of the same
+    /// name.
+    pub(crate) fn query(&self, stmt: &mut Statement) -> InternalResult<Value> {
+        for idx in 1..=stmt.parameter_count() {
+            let name = stmt.parameter_name(idx)
+                .into_internal(format!("Query parameter {idx} must be named"))?
+                .to_owned();
+            // parameter names cannot contain '.', so `$params::id` binds
+            // the variable `params.id`
+            let key = name.trim_start_matches([ ':', '@', '$', ]).replace("::", ".");
+            let value = self.value(&key)
+                .into_internal(format!("Query parameter '{name}' was never set"))?;
+
+            stmt.raw_bind_parameter(idx, value)
+                .into_internal(format!("Failed to bind query parameter '{name}'"))?;
+        }
+
+        let columns = stmt.column_names()
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<Vec<String>>();
+
+        let mut rows = Rows::default();
+        let mut query = stmt.raw_query();
+        while let Some(sql_row) = query.next().into_internal("Failed to step through query")? {
+            let mut row = Row::default();
+
+            for (idx, column) in columns.iter().enumerate() {
+                row.insert(column, Value::from_row(sql_row, idx)?)?;
+            }
+
+            rows.push(row);
+        }
+
+        Ok(Value::Rows(rows))
+    }
+
+    /// Set how the output of `{{ }}` tags is escaped. When never set, the
+    /// escape mode is chosen by the extension of the compiled file.
+    ///
+    /// # Arguments
+    ///
+    /// * `escape` - The [escape mode](Escape).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tenplates_core::{ Context, Escape, Tenplates, };
+    ///
+    /// let mut ctx = Context::default();
+    /// ctx.add_variable("x", "./", "<b>");
+    /// ctx.set_escape(Escape::Html);
+    /// let mut output = Vec::<u8>::new();
+    /// Tenplates::compile_with_ctx("{{ x }}", &mut output, ctx).unwrap();
+    /// assert_eq!("&lt;b&gt;", String::from_utf8(output).unwrap());
+    /// ```
+    ///
+    pub fn set_escape(&mut self, escape: Escape) {
+        self.escape = Some(escape);
+    }
+
+    pub(crate) fn escape(&self) -> Escape {
+        self.escape.unwrap_or_default()
+    }
+
+    pub(crate) fn default_escape<P: AsRef<Path>>(&mut self, path: P) {
+        if self.escape.is_none() {
+            self.escape = Some(Escape::from_path(path));
+        }
+    }
+
     pub(crate) fn function<K: AsRef<str>>(&self, key: K) -> Option<&Function> {
         self.functions.get(key.as_ref()).and_then(|fns| fns.last())
     }
 
-    pub(crate) fn add_function(&mut self, name: String, args: Vec<String>, content: String) {
+    pub(crate) fn add_function(&mut self, name: String, params: Vec<Param>, content: String) {
         if !self.functions.contains_key(&name) {
             self.functions.insert(name.to_owned(), Vec::new());
         }
 
-        self.functions.get_mut(&name).unwrap().push(Function::new(args, content));
+        self.functions.get_mut(&name).unwrap().push(Function::new(params, content));
     }
 }
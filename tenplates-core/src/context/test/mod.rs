@@ -1,4 +1,7 @@
-use crate::context::{ Context, Variable, };
+use crate::{
+    context::{ Arg, Context, Function, Param, Variable, },
+    value::{ Map, Value, },
+};
 
 #[test]
 fn source_from_file_1() {
@@ -24,3 +27,152 @@ fn parse_2() {
     assert_eq!("4", ctx.value("main.list").unwrap());
     assert_eq!(&"3", ctx.values("main.list").unwrap().get(2).unwrap());
 }
+
+#[test]
+fn object_1() {
+    let mut ctx = Context::default();
+    ctx.add_variable("person.name", "./", "Frankie");
+    ctx.add_variable("person.address.city", "./", "Chicago");
+
+    let person = ctx.object("person").unwrap();
+    assert_eq!("Frankie", person.get("name").unwrap());
+    assert_eq!("Chicago", person.get("address").unwrap().get("city").unwrap());
+    assert!(ctx.object("nobody").is_none());
+}
+
+#[test]
+fn object_2() {
+    let mut ctx = Context::default();
+    ctx.add_variable("person.name", "./", "Frankie");
+
+    let person = ctx.object("person").unwrap();
+    ctx.add_variable("copy", "./", person);
+
+    assert_eq!("Frankie", ctx.value("copy.name").unwrap());
+    assert!(ctx.value("copy.age").is_none());
+}
+
+#[test]
+fn object_3() {
+    let mut site = Map::default();
+    site.insert("title", "Blog");
+    site.insert("author", "Frankie");
+
+    let mut ctx = Context::default();
+    ctx.add_variable("site", "./", site);
+    ctx.add_variable("site.title", "./", "Journal");
+
+    let site = ctx.object("site").unwrap();
+    assert_eq!("Journal", site.get("title").unwrap());
+    assert_eq!("Frankie", site.get("author").unwrap());
+    assert_eq!("Journal", ctx.value("site.title").unwrap());
+}
+
+#[test]
+fn object_4() {
+    let mut ctx = Context::default();
+    ctx.add_variable("person.name", "./", "Frankie");
+    ctx.add_variable("person.address.city", "./", "Chicago");
+
+    ctx.pop_variable("person.name");
+    assert!(ctx.object("person").unwrap().get("name").is_none());
+
+    ctx.unset_variable("person.address");
+    assert!(ctx.object("person").is_none());
+    assert!(ctx.members("person").is_empty());
+}
+
+#[test]
+fn object_5() {
+    let mut ctx = Context::default();
+    ctx.add_variable("user.name", "./", "outer");
+    ctx.add_variable("user.age", "./", 41_i64);
+
+    let mut map = Map::default();
+    map.insert("name", "inner");
+    ctx.add_variable("user", "./", map);
+    ctx.add_variable("user.email", "./", "a@b.c");
+
+    assert_eq!("inner", ctx.value("user.name").unwrap());
+    assert!(ctx.value("user.age").is_none());
+
+    let object = ctx.object("user").unwrap();
+    assert_eq!("inner", object.get("name").unwrap());
+    assert_eq!("a@b.c", object.get("email").unwrap());
+    assert!(object.get("age").is_none());
+
+    ctx.pop_variable("user");
+    assert_eq!("outer", ctx.value("user.name").unwrap());
+    assert!(ctx.object("user").unwrap().get("age").is_some());
+}
+
+#[test]
+fn unset_variable_1() {
+    let mut ctx = Context::default();
+    ctx.add_variable("person", "./", "Frankie");
+    ctx.add_variable("person", "./", "Baffa");
+    ctx.add_variable("person.name", "./", "Frankie");
+    ctx.add_variable("personal", "./", "Yes");
+
+    ctx.unset_variable("person");
+
+    assert!(ctx.value("person").is_none());
+    assert!(ctx.value("person.name").is_none());
+    assert_eq!("Yes", ctx.value("personal").unwrap());
+}
+
+fn card() -> Function {
+    Function::new(vec![
+        Param::Required(String::from("title")),
+        Param::Default(String::from("class"), Value::from("default")),
+        Param::Variadic(String::from("rest")),
+    ], String::new())
+}
+
+fn arg(name: Option<&str>, value: &str) -> Arg {
+    Arg { name: name.map(String::from), value: Some(Value::from(value)), }
+}
+
+#[test]
+fn bind_1() {
+    let values = card().bind("card", vec![arg(None, "Title")]).unwrap();
+
+    assert_eq!("title", values[0].0);
+    assert_eq!("Title", values[0].1.as_ref().unwrap());
+    assert_eq!("default", values[1].1.as_ref().unwrap());
+    assert!(matches!(&values[2].1, Some(Value::List(rest)) if rest.len() == 0));
+}
+
+#[test]
+fn bind_2() {
+    let values = card().bind("card", vec![
+        arg(None, "Title"), arg(None, "wide"), arg(None, "a"), arg(None, "b"),
+    ]).unwrap();
+
+    assert_eq!("wide", values[1].1.as_ref().unwrap());
+    match &values[2].1 {
+        Some(Value::List(rest)) => {
+            assert_eq!(2, rest.len());
+            assert_eq!("b", rest.get(1).unwrap());
+        },
+        _ => panic!("Expected a list"),
+    }
+}
+
+#[test]
+fn bind_3() {
+    let values = card().bind("card", vec![arg(Some("class"), "wide")]).unwrap();
+
+    assert!(values[0].1.is_none());
+    assert_eq!("wide", values[1].1.as_ref().unwrap());
+}
+
+#[test]
+fn bind_4() {
+    let function = Function::new(vec![Param::Required(String::from("title"))], String::new());
+
+    assert!(function.bind("h", vec![arg(None, "a"), arg(None, "b")]).is_err());
+    assert!(function.bind("h", vec![arg(Some("text"), "a")]).is_err());
+    assert!(function.bind("h", vec![arg(None, "a"), arg(Some("title"), "b")]).is_err());
+    assert!(card().bind("card", vec![arg(Some("rest"), "a")]).is_err());
+}
@@ -0,0 +1,245 @@
+#[cfg(test)]
+mod test;
+
+use {
+    crate::{
+        context::Context,
+        error::{ InternalError, InternalResult, IntoInternal, },
+        value::{ List, Map, Value, },
+    },
+    serde_json::{ Map as JsonMap, Value as Json, },
+    std::{
+        fs::{ File, read_to_string, },
+        io::{ BufRead, BufReader, },
+        path::Path,
+        str::FromStr,
+    },
+    toml::Value as Toml,
+};
+
+/// The format of a data file, i.e. the `csv` in
+/// `{% load rows from "./rows.txt" as csv /%}`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Format {
+    Csv,
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// The format of a data file at `path` judged by its extension.
+    pub(crate) fn from_path<P: AsRef<Path>>(path: P) -> InternalResult<Self> {
+        let path = path.as_ref();
+        let ext = path.extension()
+            .and_then(|ext| ext.to_str())
+            .into_internal(format!("Data file {path:?} has no extension"))?;
+
+        ext.to_lowercase().parse()
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Toml => "toml",
+            Self::Yaml => "yaml",
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = InternalError;
+
+    fn from_str(input: &str) -> InternalResult<Self> {
+        match input {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            "yaml"|"yml" => Ok(Self::Yaml),
+            _ => Err(InternalError::new(format!("Unknown data format '{input}'"))),
+        }
+    }
+}
+
+/// Converts a JSON value into a [Value], keeping objects and arrays whole.
+fn from_json(json: Json) -> Value {
+    match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::from(b as i64),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::from(i),
+            None => Value::from(n.as_f64()),
+        },
+        Json::String(s) => Value::from(s),
+        Json::Array(a) => Value::from(List::from(a.into_iter()
+            .map(from_json)
+            .collect::<Vec<Value>>())),
+        Json::Object(o) => {
+            let mut map = Map::default();
+            for (key, value) in o {
+                map.insert(key, from_json(value));
+            }
+
+            Value::from(map)
+        },
+    }
+}
+
+/// Converts a TOML value into JSON, writing datetimes as text.
+fn from_toml(toml: Toml) -> Json {
+    match toml {
+        Toml::String(s) => Json::from(s),
+        Toml::Integer(i) => Json::from(i),
+        Toml::Float(f) => Json::from(f),
+        Toml::Boolean(b) => Json::from(b),
+        Toml::Datetime(d) => Json::from(d.to_string()),
+        Toml::Array(a) => Json::from(a.into_iter()
+            .map(from_toml)
+            .collect::<Vec<Json>>()),
+        Toml::Table(t) => Json::from(t.into_iter()
+            .map(|(key, value)| (key, from_toml(value)))
+            .collect::<JsonMap<String, Json>>()),
+    }
+}
+
+/// Parses CSV with a header row into an array holding an object for each
+/// record, keyed by the headers.
+fn from_csv(content: &str) -> csv::Result<Json> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.to_owned();
+
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+
+        records.push(Json::from(headers.iter()
+            .zip(record.iter())
+            .map(|(header, field)| (header.to_owned(), Json::from(field)))
+            .collect::<JsonMap<String, Json>>()));
+    }
+
+    Ok(Json::from(records))
+}
+
+fn parse(content: &str, format: Format, path: &Path) -> InternalResult<Json> {
+    let message = format!("Failed to parse data file {path:?} as {}", format.name());
+
+    match format {
+        Format::Csv => from_csv(content).into_internal(message),
+        Format::Json => serde_json::from_str(content).into_internal(message),
+        Format::Toml => toml::from_str(content).map(from_toml).into_internal(message),
+        Format::Yaml => serde_yaml::from_str(content).into_internal(message),
+    }
+}
+
+/// Stores `json` in `context` as `alias`. The members of an object are
+/// stored as dotted variables beneath `alias` and the elements of an array
+/// are stored as the values of `alias` in order.
+fn insert_json<P: AsRef<Path>>(context: &mut Context, alias: &str, path: P, json: Json) {
+    match json {
+        Json::Object(o) => for (key, value) in o {
+            insert_json(context, &format!("{alias}.{key}"), path.as_ref(), value);
+        },
+        Json::Array(a) => for value in a {
+            context.add_variable(alias, path.as_ref(), from_json(value));
+        },
+        json => context.add_variable(alias, path, from_json(json)),
+    }
+}
+
+/// Loads the data file at `path` into `context` as `alias`. The format is
+/// judged by the file's extension unless one is given.
+pub(crate) fn load<P: AsRef<Path>>(
+    context: &mut Context, alias: &str, path: P, format: Option<Format>,
+) -> InternalResult<()> {
+    let path = path.as_ref();
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(path)?,
+    };
+
+    let content = read_to_string(path)
+        .into_internal(format!("Failed to read data file {path:?}"))?;
+    let json = parse(&content, format, path)?;
+
+    insert_json(context, alias, path, json);
+
+    Ok(())
+}
+
+/// Reads the front matter block at the start of `source`, i.e. YAML between
+/// `---` lines or TOML between `+++` lines, leaving `source` at the first line
+/// after the block. Returns the front matter and the number of lines read.
+pub(crate) fn read_front_matter<R: BufRead>(
+    source: &mut R, path: &Path,
+) -> InternalResult<Option<(Json, usize)>> {
+    let buffer = source.fill_buf()
+        .into_internal(format!("Failed to read file {path:?}"))?;
+
+    let (delimiter, format) = if buffer.starts_with(b"---\n") || buffer.starts_with(b"---\r\n") {
+        ("---", Format::Yaml)
+    }
+    else if buffer.starts_with(b"+++\n") || buffer.starts_with(b"+++\r\n") {
+        ("+++", Format::Toml)
+    }
+    else {
+        return Ok(None);
+    };
+
+    let mut content = String::new();
+    let mut lines = 0;
+    loop {
+        let mut line = String::new();
+        let read = source.read_line(&mut line)
+            .into_internal(format!("Failed to read front matter in {path:?}"))?;
+
+        if read == 0 {
+            return Err(InternalError::new(format!("Unterminated front matter in {path:?}")));
+        }
+
+        lines += 1;
+
+        if lines == 1 {
+            continue;
+        }
+
+        if line.trim_end() == delimiter {
+            break;
+        }
+
+        content.push_str(&line);
+    }
+
+    // an empty block is an empty object rather than null
+    let json = if content.trim().is_empty() {
+        Json::from(JsonMap::new())
+    }
+    else {
+        parse(&content, format, path)?
+    };
+
+    Ok(Some((json, lines)))
+}
+
+/// The front matter of the file at `path` as an object, which is empty when
+/// the file has none.
+pub(crate) fn front_matter<P: AsRef<Path>>(path: P) -> InternalResult<Value> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .into_internal(format!("Failed to open file {path:?}"))?;
+
+    match read_front_matter(&mut BufReader::new(file), path)? {
+        Some((json, _)) => Ok(from_json(json)),
+        None => Ok(Value::from(Map::default())),
+    }
+}
+
+/// Stores each member of the front matter `json` in `context` as a variable.
+pub(crate) fn insert_front_matter<P: AsRef<Path>>(context: &mut Context, path: P, json: Json) {
+    if let Json::Object(o) = json {
+        for (key, value) in o {
+            insert_json(context, &key, path.as_ref(), value);
+        }
+    }
+}
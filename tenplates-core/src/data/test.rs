@@ -0,0 +1,75 @@
+use crate::{
+    context::Context,
+    data::{ Format, front_matter, load, },
+    value::Number,
+};
+
+#[test]
+fn load_json_1() {
+    let mut ctx = Context::default();
+    load(&mut ctx, "data", "./resources/data/load_json_1/posts.json", None).unwrap();
+
+    assert_eq!("My Blog", ctx.value("data.title").unwrap());
+    assert_eq!(Some(Number::Integer(3)), ctx.value("data.meta.count").unwrap().as_number());
+    assert_eq!(3, ctx.values("data.tags").unwrap().len());
+    assert_eq!("Second", ctx.value("data.posts").unwrap().get("title").unwrap());
+}
+
+#[test]
+fn load_json_2() {
+    let mut ctx = Context::default();
+    assert!(load(&mut ctx, "data", "./resources/data/load_json_2/broken.json", None).is_err());
+    assert!(load(&mut ctx, "data", "./resources/data/load_json_2/missing.json", None).is_err());
+}
+
+#[test]
+fn load_toml_1() {
+    let mut ctx = Context::default();
+    load(&mut ctx, "site", "./resources/data/load_toml_1/site.toml", None).unwrap();
+
+    assert_eq!("My Blog", ctx.value("site.title").unwrap());
+    assert_eq!("1979-05-27", ctx.value("site.published").unwrap());
+    assert_eq!("Frankie", ctx.value("site.author.name").unwrap());
+    assert_eq!("/about", ctx.values("site.links").unwrap()[0].get("href").unwrap());
+}
+
+#[test]
+fn load_yaml_1() {
+    let mut ctx = Context::default();
+    load(&mut ctx, "strings", "./resources/data/load_yaml_1/strings.yaml", None).unwrap();
+
+    assert_eq!("Hello", ctx.value("strings.greeting").unwrap());
+    assert_eq!("About", ctx.value("strings.nav.about").unwrap());
+    assert_eq!(2, ctx.values("strings.languages").unwrap().len());
+}
+
+#[test]
+fn load_csv_1() {
+    let mut ctx = Context::default();
+    load(&mut ctx, "people", "./resources/data/load_csv_1/people.csv", None).unwrap();
+
+    let people = ctx.values("people").unwrap();
+    assert_eq!(2, people.len());
+    assert_eq!("Frankie", people[0].get("name").unwrap());
+    assert_eq!("41", people[1].get("age").unwrap());
+    assert_eq!("Smith, Jo", ctx.value("people.name").unwrap());
+}
+
+#[test]
+fn format_1() {
+    assert_eq!(Format::Yaml, Format::from_path("./strings.yml").unwrap());
+    assert_eq!(Format::Json, Format::from_path("./DATA.JSON").unwrap());
+    assert!(Format::from_path("./data").is_err());
+    assert!("xml".parse::<Format>().is_err());
+}
+
+#[test]
+fn front_matter_1() {
+    let meta = front_matter("./resources/parse_front_matter_1/posts/b.md").unwrap();
+    assert_eq!("Second Post", meta.get("title").unwrap());
+
+    let meta = front_matter("./resources/parse_front_matter_1/posts/c.txt").unwrap();
+    assert!(meta.get("title").is_none());
+
+    assert!(front_matter("./resources/data/front_matter_1/unterminated.md").is_err());
+}
@@ -0,0 +1,345 @@
+#[cfg(test)]
+mod test;
+
+use {
+    base64::prelude::{ BASE64_STANDARD, Engine, },
+    crate::{
+        error::{ InternalError, InternalResult, IntoInternal, },
+        value::{ Number, ToOutput, Value, },
+    },
+    std::{ path::Path, str::FromStr, },
+};
+
+/// How the output of `{{ }}` tags is escaped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Escape {
+    /// Output is written as-is.
+    #[default]
+    None,
+    /// Output is HTML-escaped.
+    Html,
+}
+
+impl Escape {
+    /// The escape mode for a template at `path`, i.e. [Html](Escape::Html)
+    /// for `page.html` or `page.html.tenplate`.
+    pub(crate) fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let is_html = path.as_ref().file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.split('.')
+                .skip(1)
+                .any(|ext| matches!(ext.to_lowercase().as_str(), "html"|"htm")));
+
+        if is_html {
+            Self::Html
+        }
+        else {
+            Self::None
+        }
+    }
+
+    pub(crate) fn apply(&self, input: String) -> String {
+        match self {
+            Self::None => input,
+            Self::Html => escape_html(&input),
+        }
+    }
+}
+
+impl FromStr for Escape {
+    type Err = InternalError;
+
+    fn from_str(input: &str) -> InternalResult<Self> {
+        match input {
+            "none" => Ok(Self::None),
+            "html" => Ok(Self::Html),
+            _ => Err(InternalError::new(format!("Unknown escape mode '{input}'"))),
+        }
+    }
+}
+
+/// A filter applied to a value in an output tag, i.e. the `upper` in
+/// `{{ title | upper }}`. Filters are applied from left to right, each
+/// receiving the value produced by the last.
+#[derive(Clone, Debug)]
+pub(crate) enum Filter {
+    // string
+    Capitalize,
+    Default(Value),
+    Length,
+    Lower,
+    Replace(String, String),
+    Title,
+    Trim,
+    Truncate(usize, String),
+    Upper,
+    // number
+    Abs,
+    Ceil,
+    Fixed(usize),
+    Floor,
+    Round,
+    // encoding
+    Base64,
+    Escape,
+    Json,
+    UrlEncode,
+    Raw,
+}
+
+fn expect_args(name: &str, args: &[Value], min: usize, max: usize) -> InternalResult<()> {
+    if args.len() < min || args.len() > max {
+        let expected = if min == max {
+            min.to_string()
+        }
+        else {
+            format!("{min} to {max}")
+        };
+
+        return Err(InternalError::new(format!(
+            "Filter '{name}' expects {expected} argument(s) but received {}", args.len()
+        )));
+    }
+
+    Ok(())
+}
+
+fn arg_as_usize(name: &str, arg: &Value) -> InternalResult<usize> {
+    match arg.as_number() {
+        Some(Number::Integer(i)) => usize::try_from(i)
+            .into_internal(format!("Filter '{name}' expects a positive integer argument")),
+        _ => Err(InternalError::new(format!("Filter '{name}' expects an integer argument"))),
+    }
+}
+
+fn number(name: &str, value: &Value) -> InternalResult<Number> {
+    value.as_number()
+        .into_internal(format!("Filter '{name}' can only be applied to a number"))
+}
+
+fn text(value: &Value) -> InternalResult<String> {
+    value.to_output(&[])
+}
+
+/// Escapes the characters which are significant in HTML.
+pub(crate) fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn url_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+
+    for b in input.bytes() {
+        match b {
+            b'A'..=b'Z'|b'a'..=b'z'|b'0'..=b'9'|b'-'|b'_'|b'.'|b'~' => encoded.push(b as char),
+            b => encoded.push_str(&format!("%{b:02X}")),
+        }
+    }
+
+    encoded
+}
+
+fn json_string(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len() + 2);
+    encoded.push('"');
+
+    for c in input.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\r' => encoded.push_str("\\r"),
+            '\t' => encoded.push_str("\\t"),
+            c if (c as u32) < 0x20 => encoded.push_str(&format!("\\u{:04x}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+
+    encoded.push('"');
+    encoded
+}
+
+/// Encodes `value` as JSON, where maps and rows are objects and lists and
+/// rows are arrays.
+fn json(value: &Value) -> InternalResult<String> {
+    Ok(match value {
+        Value::Null => String::from("null"),
+        Value::Integer(_)|Value::Real(_) => match text(value)? {
+            number if number.is_empty() => String::from("null"),
+            number => number,
+        },
+        Value::Map(_)|Value::Row(_) => {
+            let members = value.entries()
+                .iter()
+                .map(|(key, member)| Ok(format!("{}:{}", json_string(&text(key)?), json(member)?)))
+                .collect::<InternalResult<Vec<String>>>()?;
+
+            format!("{{{}}}", members.join(","))
+        },
+        Value::List(_)|Value::Rows(_) => {
+            let elements = value.entries()
+                .iter()
+                .map(|(_, element)| json(element))
+                .collect::<InternalResult<Vec<String>>>()?;
+
+            format!("[{}]", elements.join(","))
+        },
+        v => json_string(&text(v)?),
+    })
+}
+
+fn capitalize(input: &str) -> String {
+    let mut chars = input.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn rounded(name: &str, value: &Value, round: fn(f64) -> f64) -> InternalResult<Value> {
+    match number(name, value)? {
+        Number::Integer(i) => Ok(Value::from(i)),
+        Number::Real(r) => {
+            let r = round(r);
+
+            // stay an integer wherever the result fits in one
+            if r >= i64::MIN as f64 && r < i64::MAX as f64 {
+                Ok(Value::from(r as i64))
+            }
+            else {
+                Ok(Value::from(r))
+            }
+        },
+    }
+}
+
+impl Filter {
+    pub(crate) fn new(name: &str, mut args: Vec<Value>) -> InternalResult<Self> {
+        let filter = match name {
+            "capitalize" => Self::Capitalize,
+            "default" => {
+                expect_args(name, &args, 1, 1)?;
+                return Ok(Self::Default(args.remove(0)));
+            },
+            "length" => Self::Length,
+            "lower" => Self::Lower,
+            "replace" => {
+                expect_args(name, &args, 2, 2)?;
+                return Ok(Self::Replace(text(&args[0])?, text(&args[1])?));
+            },
+            "title" => Self::Title,
+            "trim" => Self::Trim,
+            "truncate" => {
+                expect_args(name, &args, 1, 2)?;
+                let suffix = match args.get(1) {
+                    Some(suffix) => text(suffix)?,
+                    None => String::new(),
+                };
+
+                return Ok(Self::Truncate(arg_as_usize(name, &args[0])?, suffix));
+            },
+            "upper" => Self::Upper,
+            "abs" => Self::Abs,
+            "ceil" => Self::Ceil,
+            "fixed" => {
+                expect_args(name, &args, 1, 1)?;
+                return Ok(Self::Fixed(arg_as_usize(name, &args[0])?));
+            },
+            "floor" => Self::Floor,
+            "round" => Self::Round,
+            "base64" => Self::Base64,
+            "escape" => Self::Escape,
+            "json" => Self::Json,
+            "urlencode" => Self::UrlEncode,
+            "raw" => Self::Raw,
+            _ => return Err(InternalError::new(format!("Unknown filter '{name}'"))),
+        };
+
+        expect_args(name, &args, 0, 0)?;
+
+        Ok(filter)
+    }
+
+    /// Whether the output of this filter should not be escaped again.
+    pub(crate) fn is_escaped(&self) -> bool {
+        matches!(self, Self::Escape|Self::Raw)
+    }
+
+    pub(crate) fn apply(&self, value: &Value) -> InternalResult<Value> {
+        Ok(match self {
+            Self::Capitalize => Value::from(capitalize(&text(value)?)),
+            Self::Default(default) => match value {
+                Value::Null => default.to_owned(),
+                v if text(v)?.is_empty() => default.to_owned(),
+                v => v.to_owned(),
+            },
+            Self::Length => match value {
+                Value::Row(_)|Value::Rows(_) => Value::from(value.to_owned().into_vec().len() as i64),
+                Value::Map(m) => Value::from(m.len() as i64),
+                Value::List(l) => Value::from(l.len() as i64),
+                Value::Null => Value::from(0_i64),
+                v => Value::from(text(v)?.chars().count() as i64),
+            },
+            Self::Lower => Value::from(text(value)?.to_lowercase()),
+            Self::Replace(from, to) => Value::from(text(value)?.replace(from, to)),
+            Self::Title => Value::from(text(value)?
+                .split(' ')
+                .map(capitalize)
+                .collect::<Vec<String>>()
+                .join(" ")),
+            Self::Trim => Value::from(text(value)?.trim()),
+            Self::Truncate(length, suffix) => {
+                let text = text(value)?;
+
+                if text.chars().count() > *length {
+                    let mut truncated = text.chars().take(*length).collect::<String>();
+                    truncated.push_str(suffix);
+                    Value::from(truncated)
+                }
+                else {
+                    Value::from(text)
+                }
+            },
+            Self::Upper => Value::from(text(value)?.to_uppercase()),
+            Self::Abs => match number("abs", value)? {
+                Number::Integer(i) => Value::from(i.checked_abs()
+                    .into_internal(format!("Absolute value of {i} overflowed"))?),
+                Number::Real(r) => Value::from(r.abs()),
+            },
+            Self::Ceil => rounded("ceil", value, f64::ceil)?,
+            Self::Fixed(places) => {
+                let number = match number("fixed", value)? {
+                    Number::Integer(i) => i as f64,
+                    Number::Real(r) => r,
+                };
+
+                Value::from(format!("{number:.places$}"))
+            },
+            Self::Floor => rounded("floor", value, f64::floor)?,
+            Self::Round => rounded("round", value, f64::round)?,
+            Self::Base64 => match value {
+                Value::Blob(_) => Value::from(text(value)?),
+                v => Value::from(BASE64_STANDARD.encode(text(v)?)),
+            },
+            Self::Escape => Value::from(escape_html(&text(value)?)),
+            Self::Json => Value::from(json(value)?),
+            Self::UrlEncode => Value::from(url_encode(&text(value)?)),
+            // only marks the value as safe to output unescaped
+            Self::Raw => value.to_owned(),
+        })
+    }
+}
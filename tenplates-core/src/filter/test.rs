@@ -0,0 +1,106 @@
+use crate::{
+    filter::{ escape_html, Escape, Filter, },
+    value::{ List, Map, ToOutput, Value, },
+};
+
+fn apply(value: Value, filters: &[Filter]) -> String {
+    value.to_output(filters).unwrap()
+}
+
+#[test]
+fn upper_1() {
+    let filters = [ Filter::new("upper", vec![]).unwrap() ];
+    assert_eq!("HELLO, WORLD!", apply(Value::from("Hello, World!"), &filters));
+}
+
+#[test]
+fn truncate_1() {
+    let filters = [ Filter::new("truncate", vec![ Value::from(5_i64) ]).unwrap() ];
+    assert_eq!("Hello", apply(Value::from("Hello, World!"), &filters));
+}
+
+#[test]
+fn truncate_2() {
+    let filters = [ Filter::new("truncate", vec![ Value::from(5_i64), Value::from("...") ]).unwrap() ];
+    assert_eq!("Hello...", apply(Value::from("Hello, World!"), &filters));
+    assert_eq!("Hi", apply(Value::from("Hi"), &filters));
+}
+
+#[test]
+fn fixed_1() {
+    let filters = [ Filter::new("fixed", vec![ Value::from(2_i64) ]).unwrap() ];
+    assert_eq!("12.35", apply(Value::from(12.3456_f64), &filters));
+    assert_eq!("4.00", apply(Value::from("4"), &filters));
+}
+
+#[test]
+fn fixed_2() {
+    let filters = [ Filter::new("fixed", vec![ Value::from(2_i64) ]).unwrap() ];
+    assert!(Value::from("four").to_output(&filters).is_err());
+}
+
+#[test]
+fn round_1() {
+    let filters = [ Filter::new("round", vec![]).unwrap() ];
+    assert_eq!("3", apply(Value::from(2.5_f64), &filters));
+}
+
+#[test]
+fn default_1() {
+    let filters = [ Filter::new("default", vec![ Value::from("n/a") ]).unwrap() ];
+    assert_eq!("n/a", apply(Value::Null, &filters));
+    assert_eq!("n/a", apply(Value::from(""), &filters));
+    assert_eq!("0", apply(Value::from(0_i64), &filters));
+}
+
+#[test]
+fn chain_1() {
+    let filters = [
+        Filter::new("trim", vec![]).unwrap(),
+        Filter::new("lower", vec![]).unwrap(),
+        Filter::new("replace", vec![ Value::from(" "), Value::from("-") ]).unwrap(),
+        Filter::new("urlencode", vec![]).unwrap(),
+    ];
+    assert_eq!("a-%26-b", apply(Value::from("  A & B "), &filters));
+}
+
+#[test]
+fn json_1() {
+    let filters = [ Filter::new("json", vec![]).unwrap() ];
+    assert_eq!("\"say \\\"hi\\\"\\n\"", apply(Value::from("say \"hi\"\n"), &filters));
+    assert_eq!("12", apply(Value::from(12_i64), &filters));
+    assert_eq!("null", apply(Value::Null, &filters));
+}
+
+#[test]
+fn json_2() {
+    let filters = [ Filter::new("json", vec![]).unwrap() ];
+    let mut map = Map::default();
+    map.insert("name", "Frankie");
+    map.insert("tags", List::from(vec![ Value::from("a"), Value::from(1_i64), Value::Null, ]));
+    assert_eq!("{\"name\":\"Frankie\",\"tags\":[\"a\",1,null]}", apply(Value::from(map), &filters));
+}
+
+#[test]
+fn unknown_1() {
+    assert!(Filter::new("shout", vec![]).is_err());
+}
+
+#[test]
+fn arguments_1() {
+    assert!(Filter::new("upper", vec![ Value::from(1_i64) ]).is_err());
+    assert!(Filter::new("truncate", vec![]).is_err());
+}
+
+#[test]
+fn escape_html_1() {
+    assert_eq!("&lt;a href=&quot;#&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;", escape_html("<a href=\"#\">Tom & Jerry's</a>"));
+}
+
+#[test]
+fn escape_from_path_1() {
+    assert_eq!(Escape::Html, Escape::from_path("./site/index.html"));
+    assert_eq!(Escape::Html, Escape::from_path("./site/index.html.tenplate"));
+    assert_eq!(Escape::None, Escape::from_path("./site/index.tenplate"));
+    assert_eq!(Escape::None, Escape::from_path("./site.html/index.txt"));
+}
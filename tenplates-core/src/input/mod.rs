@@ -2,7 +2,10 @@
 mod test;
 
 use {
-    crate::error::{ IntoInternal, InternalResult, },
+    crate::{
+        data::read_front_matter,
+        error::{ IntoInternal, InternalResult, },
+    },
     std::{
         fmt::Debug,
         fs::{ OpenOptions, File, },
@@ -133,6 +136,32 @@ impl TryIntoInput<File> for &Path {
     }
 }
 
+impl Input<File> {
+    /// Opens the template at `path`, skipping its front matter so that it is
+    /// never written to the output.
+    pub(crate) fn template<P: AsRef<Path>>(path: P) -> InternalResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .into_internal(format!("Failed to open file at {path:?}"))?;
+
+        let mut source = BufReader::new(file);
+        let line = read_front_matter(&mut source, path)?
+            .map_or(0, |(_, lines)| lines);
+
+        let mut input = Input::<File> {
+            path: path.to_owned(),
+            source,
+            read: None,
+            line,
+            index: 0,
+        };
+
+        input.step()?;
+
+        Ok(input)
+    }
+}
+
 impl TryIntoInput<File> for PathBuf {
     fn try_into_input(self) -> InternalResult<Input<File>> {
         let path: &Path = self.as_ref();
@@ -194,6 +223,12 @@ where
         self.path = path.as_ref().into();
     }
 
+    /// Offsets the line number by `lines` which were read from the source
+    /// before it became input, i.e. front matter.
+    pub(crate) fn offset_lines(&mut self, lines: usize) {
+        self.line += lines;
+    }
+
     pub(crate) fn step(&mut self) -> InternalResult<()> {
         if let Some(is) = self.read.as_mut() {
             is.step();
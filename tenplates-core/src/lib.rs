@@ -15,25 +15,33 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod context;
+mod data;
 mod error;
+mod filter;
 mod input;
+mod listing;
 mod macros;
 mod output;
 mod parser;
+mod value;
 
 pub use {
-    context::Context,
+    context::{ Alias, Context, },
     error::{ InternalResult, InternalError, },
+    filter::Escape,
+    value::Value,
 };
 
 use {
     crate::{
+        error::IntoInternal,
         input::TryIntoInput,
         parser::TemplateParser,
     },
     std::{
         fmt::Debug,
-        io::{ Read, stdout, Write, },
+        fs::File,
+        io::{ BufReader, Read, stdout, Write, },
         path::Path,
     },
 };
@@ -106,6 +114,8 @@ impl Tenplates {
     }
 
     /// Compile a template file to a given output with a specific context.
+    /// Each value in the file's front matter is set as a variable in context
+    /// before the rest of the file is compiled.
     ///
     /// # Arguments
     ///
@@ -126,15 +136,34 @@ impl Tenplates {
     /// assert_eq!("The number: 4", output_str);
     /// ```
     ///
-    pub fn compile_file_with_ctx<P, W>(path: P, output: W, ctx: Context) -> InternalResult<()>
+    pub fn compile_file_with_ctx<P, W>(path: P, output: W, mut ctx: Context) -> InternalResult<()>
     where
         P: AsRef<Path>,
         W: Write + Debug,
     {
-        Self::compile_with_ctx(path.as_ref(), output, ctx)
+        let path = path.as_ref();
+        let file = File::open(path)
+            .into_internal(format!("Failed to open file at {path:?}"))?;
+        let mut source = BufReader::new(file);
+
+        let lines = match data::read_front_matter(&mut source, path)? {
+            Some((front_matter, lines)) => {
+                data::insert_front_matter(&mut ctx, path, front_matter);
+                lines
+            },
+            None => 0,
+        };
+
+        let mut input = source.try_into_input()?;
+        input.set_path(path);
+        input.offset_lines(lines);
+
+        Self::compile_with_ctx(input, output, ctx)
     }
 
-    /// Compile a template file to a given output.
+    /// Compile a template file to a given output. Each value in the file's
+    /// front matter is set as a variable in context before the rest of the
+    /// file is compiled.
     ///
     /// # Arguments
     ///
@@ -158,7 +187,7 @@ impl Tenplates {
         P: AsRef<Path>,
         W: Write + Debug,
     {
-        Self::compile(path.as_ref(), output)
+        Self::compile_file_with_ctx(path, output, Context::default())
     }
 
     /// Compile an input template to stdout with a specific context.
@@ -268,4 +297,12 @@ mod test {
         let output = String::from_utf8(output).unwrap();
         assert_eq!("The number: 4", output);
     }
+
+    #[test]
+    fn parse_file_2() {
+        let mut output = Vec::<u8>::new();
+        crate::Tenplates::compile_file("./resources/parse_file_2/page.tenplate", &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!("Home: /about /contact \n", output);
+    }
 }
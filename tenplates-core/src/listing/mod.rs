@@ -0,0 +1,377 @@
+#[cfg(test)]
+mod test;
+
+use {
+    crate::{
+        error::{ InternalError, InternalResult, IntoInternal, },
+        value::{ Map, Value, },
+    },
+    glob::{ MatchOptions, Pattern, },
+    std::{
+        cmp::Ordering,
+        collections::HashSet,
+        iter::Peekable,
+        path::{ Path, PathBuf, },
+        str::{ Chars, FromStr, },
+        time::{ SystemTime, UNIX_EPOCH, },
+    },
+};
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// The kind of directory entry listed by a loop tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Entry {
+    Dir,
+    File,
+}
+
+/// How the entries of a listing are ordered, i.e. the `mtime` in
+/// `sort by mtime`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Sort {
+    /// By path.
+    #[default]
+    Path,
+    /// By file name.
+    Name,
+    /// By file name, comparing runs of digits by their numeric value.
+    Natural,
+    /// By last modification time, oldest first.
+    Mtime,
+    /// By size in bytes, smallest first.
+    Size,
+    /// By extension, then by file name.
+    Ext,
+}
+
+impl FromStr for Sort {
+    type Err = InternalError;
+
+    fn from_str(input: &str) -> InternalResult<Self> {
+        match input {
+            "path" => Ok(Self::Path),
+            "name" => Ok(Self::Name),
+            "natural" => Ok(Self::Natural),
+            "mtime" => Ok(Self::Mtime),
+            "size" => Ok(Self::Size),
+            "ext" => Ok(Self::Ext),
+            _ => Err(InternalError::new(format!("Unknown sort order '{input}'"))),
+        }
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+
+    digits
+}
+
+/// Compares `a` and `b` as text, except that runs of digits are compared by
+/// their numeric value, i.e. `v2` comes before `v10`.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = digits(&mut a);
+                let y = digits(&mut b);
+                let x = x.trim_start_matches('0');
+                let y = y.trim_start_matches('0');
+
+                match x.len().cmp(&y.len()).then_with(|| x.cmp(y)) {
+                    Ordering::Equal => continue,
+                    ordering => return ordering,
+                }
+            },
+            (Some(x), Some(y)) => match x.cmp(y) {
+                Ordering::Equal => {
+                    a.next();
+                    b.next();
+                },
+                ordering => return ordering,
+            },
+        }
+    }
+}
+
+fn name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+}
+
+fn ext(path: &Path) -> &str {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+}
+
+fn mtime(path: &Path) -> InternalResult<SystemTime> {
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .into_internal(format!("Failed to read the modification time of {path:?}"))
+}
+
+fn size(path: &Path) -> InternalResult<u64> {
+    path.metadata()
+        .map(|metadata| metadata.len())
+        .into_internal(format!("Failed to read the size of {path:?}"))
+}
+
+/// Formats `time` as an ISO 8601 date and time in UTC, i.e.
+/// `2025-01-31T08:30:00Z`.
+fn iso8601(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+
+    let days = seconds.div_euclid(86400);
+    let time = seconds.rem_euclid(86400);
+
+    // the civil date of a count of days since 1970-01-01
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600, time % 3600 / 60, time % 60,
+    )
+}
+
+/// The metadata of the file or directory at `path`, i.e. its `size`,
+/// `mtime`, `ext`, `stem`, `name` and whether it `isdir`.
+pub(crate) fn stat(path: &Path) -> InternalResult<Map> {
+    let metadata = path.metadata()
+        .into_internal(format!("Failed to read the metadata of {path:?}"))?;
+
+    let mut map = Map::default();
+    map.insert("size", metadata.len() as i64);
+    map.insert("mtime", iso8601(mtime(path)?));
+    map.insert("ext", ext(path));
+    map.insert("stem", path.file_stem().and_then(|stem| stem.to_str()).unwrap_or(""));
+    map.insert("name", name(path));
+    map.insert("isdir", Value::from(metadata.is_dir() as i64));
+
+    Ok(map)
+}
+
+/// The options of a `fordir` or `forfile` tag which choose the entries
+/// listed, i.e. `matching "**/*.md" recursive` in
+/// `{% forfile f in "./content" matching "**/*.md" recursive %}`.
+#[derive(Debug)]
+pub(crate) struct Listing {
+    entry: Entry,
+    matching: Vec<Pattern>,
+    excluding: Vec<Pattern>,
+    recursive: bool,
+    depth: Option<usize>,
+    skip_hidden: bool,
+    extensions: Vec<String>,
+    sort: Sort,
+    reversed: bool,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+fn pattern(glob: &str) -> InternalResult<Pattern> {
+    Pattern::new(glob).into_internal(format!("Invalid glob pattern '{glob}'"))
+}
+
+fn canonical(path: &Path) -> InternalResult<PathBuf> {
+    path.canonicalize()
+        .into_internal(format!("Failed to resolve the directory {path:?}"))
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+impl Listing {
+    pub(crate) fn new(entry: Entry) -> Self {
+        Self {
+            entry,
+            matching: Vec::new(),
+            excluding: Vec::new(),
+            recursive: false,
+            depth: None,
+            skip_hidden: false,
+            extensions: Vec::new(),
+            sort: Sort::default(),
+            reversed: false,
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    /// Only lists entries whose path relative to the listed directory
+    /// matches `glob`, or any of the globs when given more than once.
+    pub(crate) fn matching(&mut self, glob: &str) -> InternalResult<()> {
+        self.matching.push(pattern(glob)?);
+        Ok(())
+    }
+
+    /// Never lists entries whose path relative to the listed directory
+    /// matches `glob`.
+    pub(crate) fn excluding(&mut self, glob: &str) -> InternalResult<()> {
+        self.excluding.push(pattern(glob)?);
+        Ok(())
+    }
+
+    pub(crate) fn recursive(&mut self) {
+        self.recursive = true;
+    }
+
+    /// Descends at most `depth` levels, where `1` is the listed directory
+    /// alone.
+    pub(crate) fn depth(&mut self, depth: usize) {
+        self.recursive = true;
+        self.depth = Some(depth);
+    }
+
+    /// Skips entries whose names start with a `.`, along with everything
+    /// within them.
+    pub(crate) fn skip_hidden(&mut self) {
+        self.skip_hidden = true;
+    }
+
+    /// Only lists entries with the extension `ext`, or any of the extensions
+    /// when given more than once.
+    pub(crate) fn extension(&mut self, ext: &str) {
+        self.extensions.push(ext.trim_start_matches('.').to_lowercase());
+    }
+
+    pub(crate) fn sort(&mut self, sort: Sort) {
+        self.sort = sort;
+    }
+
+    pub(crate) fn reversed(&mut self) {
+        self.reversed = true;
+    }
+
+    /// Skips the first `offset` entries after ordering.
+    pub(crate) fn offset(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    /// Lists at most `limit` entries after ordering.
+    pub(crate) fn limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+
+    fn is_listed(&self, relative: &Path, path: &Path) -> bool {
+        let is_entry = match self.entry {
+            Entry::Dir => path.is_dir(),
+            Entry::File => path.is_file(),
+        };
+
+        is_entry
+            && (self.extensions.is_empty() || self.extensions.iter()
+                .any(|wanted| wanted.eq(&ext(path).to_lowercase())))
+            && (self.matching.is_empty() || self.matching.iter()
+                .any(|pattern| pattern.matches_path_with(relative, MATCH_OPTIONS)))
+            && !self.excluding.iter()
+                .any(|pattern| pattern.matches_path_with(relative, MATCH_OPTIONS))
+    }
+
+    fn walk(
+        &self, root: &Path, dir: &Path, level: usize, visited: &mut HashSet<PathBuf>, paths: &mut Vec<PathBuf>,
+    ) -> InternalResult<()> {
+        let entries = dir.read_dir()
+            .into_internal("Failed to read directory")?;
+
+        for entry in entries {
+            let path = entry.into_internal("Failed to read paths of directory entries")?
+                .path();
+
+            if self.skip_hidden && is_hidden(&path) {
+                continue;
+            }
+
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if self.is_listed(relative, &path) {
+                paths.push(path.clone());
+            }
+
+            let descend = self.recursive
+                && self.depth.is_none_or(|depth| level < depth)
+                && path.is_dir();
+
+            // a directory linked to more than once, i.e. by a symlink to one
+            // of its parents, is only descended into the first time
+            if descend && visited.insert(canonical(&path)?) {
+                self.walk(root, &path, level + 1, visited, paths)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The absolute paths of the entries within the directory at `path` in
+    /// order.
+    pub(crate) fn list(&self, path: &Path) -> InternalResult<Vec<PathBuf>> {
+        // listing from the absolute directory keeps the paths free of `.` and
+        // `..`, and means they resolve to themselves wherever they are used
+        let root = canonical(path)?;
+        let mut paths = Vec::new();
+        let mut visited = HashSet::from([ root.clone(), ]);
+        self.walk(&root, &root, 1, &mut visited, &mut paths)?;
+
+        // ordering by path first keeps ties of the other orders predictable
+        paths.sort_unstable();
+
+        match self.sort {
+            Sort::Path => {},
+            Sort::Name => paths.sort_by(|a, b| name(a).cmp(name(b))),
+            Sort::Natural => paths.sort_by(|a, b| natural_cmp(name(a), name(b))),
+            Sort::Mtime => {
+                let mut keyed = paths.into_iter()
+                    .map(|path| mtime(&path).map(|mtime| (mtime, path)))
+                    .collect::<InternalResult<Vec<(SystemTime, PathBuf)>>>()?;
+                keyed.sort_by_key(|(key, _)| *key);
+                paths = keyed.into_iter().map(|(_, path)| path).collect();
+            },
+            Sort::Size => {
+                let mut keyed = paths.into_iter()
+                    .map(|path| size(&path).map(|size| (size, path)))
+                    .collect::<InternalResult<Vec<(u64, PathBuf)>>>()?;
+                keyed.sort_by_key(|(key, _)| *key);
+                paths = keyed.into_iter().map(|(_, path)| path).collect();
+            },
+            Sort::Ext => paths.sort_by(|a, b| ext(a).cmp(ext(b))
+                .then_with(|| name(a).cmp(name(b)))),
+        }
+
+        if self.reversed {
+            paths.reverse();
+        }
+
+        Ok(paths.into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+}
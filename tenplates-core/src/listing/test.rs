@@ -0,0 +1,188 @@
+use {
+    crate::{
+        listing::{ Entry, iso8601, Listing, natural_cmp, Sort, stat, },
+        value::Number,
+    },
+    std::{
+        cmp::Ordering,
+        env::temp_dir,
+        fs::{ create_dir_all, File, },
+        path::{ Path, PathBuf, },
+        time::{ Duration, SystemTime, UNIX_EPOCH, },
+    },
+};
+
+const SITE: &str = "./resources/listing/site";
+const VERSIONS: &str = "./resources/listing/versions";
+
+fn names(paths: Vec<PathBuf>) -> Vec<String> {
+    paths.iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+        .collect()
+}
+
+fn relative(paths: Vec<PathBuf>) -> Vec<String> {
+    let site = Path::new(SITE).canonicalize().unwrap();
+    paths.iter()
+        .map(|path| path.strip_prefix(&site).unwrap().to_str().unwrap().to_owned())
+        .collect()
+}
+
+#[test]
+fn list_1() {
+    let listing = Listing::new(Entry::File);
+    let paths = listing.list(Path::new(SITE)).unwrap();
+
+    assert_eq!(vec![ ".hidden.md", "about.txt", "index.md", ], relative(paths));
+}
+
+#[test]
+fn list_2() {
+    let mut listing = Listing::new(Entry::File);
+    listing.matching("**/*.md").unwrap();
+    listing.recursive();
+    listing.skip_hidden();
+    let paths = listing.list(Path::new(SITE)).unwrap();
+
+    assert_eq!(vec![ "blog/drafts/wip.md", "blog/post-1.md", "index.md", ], relative(paths));
+}
+
+#[test]
+fn list_3() {
+    let mut listing = Listing::new(Entry::File);
+    listing.matching("**/*.md").unwrap();
+    listing.excluding("blog/drafts/**").unwrap();
+    listing.depth(2);
+    let paths = listing.list(Path::new(SITE)).unwrap();
+
+    assert_eq!(vec![ ".hidden.md", "blog/post-1.md", "index.md", ], relative(paths));
+}
+
+#[test]
+fn list_4() {
+    let mut listing = Listing::new(Entry::Dir);
+    listing.recursive();
+    listing.reversed();
+    let paths = listing.list(Path::new(SITE)).unwrap();
+
+    assert_eq!(vec![ "blog/drafts", "blog/.secret", "blog", "assets", ], relative(paths));
+}
+
+#[test]
+fn list_5() {
+    let mut listing = Listing::new(Entry::File);
+    assert!(listing.matching("[").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn list_6() {
+    let dir = temp_dir().join("tenplates-listing-list-6");
+    create_dir_all(dir.join("a")).unwrap();
+    File::create(dir.join("a/page.md")).unwrap();
+
+    // a link back to the listed directory would otherwise never end
+    let link = dir.join("a/loop");
+    if link.symlink_metadata().is_err() {
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+    }
+
+    let mut listing = Listing::new(Entry::File);
+    listing.recursive();
+    let paths = listing.list(&dir).unwrap();
+
+    assert_eq!(vec![ "page.md", ], names(paths));
+}
+
+#[test]
+fn list_7() {
+    let site = Path::new(SITE).canonicalize().unwrap();
+    let listing = Listing::new(Entry::File);
+    let paths = listing.list(&Path::new(SITE).join("./blog/..")).unwrap();
+
+    // paths are absolute whichever way the directory was named
+    assert_eq!(vec![ site.join(".hidden.md"), site.join("about.txt"), site.join("index.md"), ], paths);
+}
+
+#[test]
+fn natural_cmp_1() {
+    assert_eq!(Ordering::Less, natural_cmp("v2", "v10"));
+    assert_eq!(Ordering::Greater, natural_cmp("v10", "v9"));
+    assert_eq!(Ordering::Equal, natural_cmp("v007", "v7"));
+    assert_eq!(Ordering::Less, natural_cmp("page", "page2"));
+}
+
+#[test]
+fn sort_1() {
+    let mut listing = Listing::new(Entry::File);
+    listing.sort(Sort::Natural);
+    let paths = listing.list(Path::new(VERSIONS)).unwrap();
+    assert_eq!(vec![ "v1.txt", "v2.md", "v9.TXT", "v10.txt", ], names(paths));
+
+    let mut listing = Listing::new(Entry::File);
+    listing.sort(Sort::Size);
+    listing.reversed();
+    let paths = listing.list(Path::new(VERSIONS)).unwrap();
+    assert_eq!(vec![ "v1.txt", "v10.txt", "v9.TXT", "v2.md", ], names(paths));
+
+    let mut listing = Listing::new(Entry::File);
+    listing.sort(Sort::Ext);
+    let paths = listing.list(Path::new(VERSIONS)).unwrap();
+    assert_eq!(vec![ "v9.TXT", "v2.md", "v1.txt", "v10.txt", ], names(paths));
+
+    assert!("random".parse::<Sort>().is_err());
+}
+
+#[test]
+fn sort_2() {
+    let dir = temp_dir().join("tenplates-listing-sort-2");
+    create_dir_all(&dir).unwrap();
+
+    let now = SystemTime::now();
+    for (name, age) in [ ("old", 300), ("new", 0), ("mid", 100), ] {
+        let file = File::create(dir.join(name)).unwrap();
+        file.set_modified(now - Duration::from_secs(age)).unwrap();
+    }
+
+    let mut listing = Listing::new(Entry::File);
+    listing.sort(Sort::Mtime);
+    listing.reversed();
+    listing.limit(2);
+    let paths = listing.list(&dir).unwrap();
+
+    assert_eq!(vec![ "new", "mid", ], names(paths));
+}
+
+#[test]
+fn filter_1() {
+    let mut listing = Listing::new(Entry::File);
+    listing.extension(".txt");
+    listing.sort(Sort::Natural);
+    listing.offset(1);
+    listing.limit(5);
+    let paths = listing.list(Path::new(VERSIONS)).unwrap();
+
+    assert_eq!(vec![ "v9.TXT", "v10.txt", ], names(paths));
+}
+
+#[test]
+fn iso8601_1() {
+    assert_eq!("1970-01-01T00:00:00Z", iso8601(UNIX_EPOCH));
+    assert_eq!("2000-02-29T12:34:56Z", iso8601(UNIX_EPOCH + Duration::from_secs(951827696)));
+    assert_eq!("1969-12-31T23:59:59Z", iso8601(UNIX_EPOCH - Duration::from_secs(1)));
+}
+
+#[test]
+fn stat_1() {
+    let file = stat(Path::new("./resources/listing/versions/v10.txt")).unwrap();
+    assert_eq!(Some(Number::Integer(3)), file.get("size").and_then(|size| size.as_number()));
+    assert_eq!("v10.txt", file.get("name").unwrap());
+    assert_eq!("v10", file.get("stem").unwrap());
+    assert_eq!("txt", file.get("ext").unwrap());
+
+    let dir = stat(Path::new(VERSIONS)).unwrap();
+    assert_eq!(Some(Number::Integer(1)), dir.get("isdir").and_then(|isdir| isdir.as_number()));
+    assert_eq!("", dir.get("ext").unwrap());
+
+    assert!(stat(Path::new("./resources/listing/missing")).is_err());
+}
@@ -37,13 +37,12 @@ where
     }
 
     pub(crate) fn write_char(&mut self, c: char) {
-        self.buffer.push(c as u8);
+        let mut bytes = [0; 4];
+        self.buffer.extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
     }
 
     pub(crate) fn write_str(&mut self, s: &str) {
-        for c in s.chars() {
-            self.write_char(c);
-        }
+        self.buffer.extend_from_slice(s.as_bytes());
     }
 
     pub(crate) fn flush_buffer_to_content(&mut self) {
@@ -17,8 +17,10 @@ use {
             TemplateParser,
             steps::*,
         },
+        value::Value,
     },
     std::{
+        cmp::Ordering,
         fmt::Debug,
         io::{ Read, Write, },
     },
@@ -51,6 +53,10 @@ impl Condition {
         }
     }
 
+    fn negate(&mut self) {
+        self.evaluation = !self.evaluation;
+    }
+
     fn set_join(&mut self, join: Join) {
         self.join = Some(join);
     }
@@ -59,51 +65,42 @@ impl Condition {
         self.evaluation
     }
 
-    fn try_num<F, H>(a: Option<String>, b: Option<String>, num_cmp: F, str_cmp: H) -> Self
+    fn try_cmp<F>(a: Option<Value>, b: Option<Value>, ord_cmp: F) -> Self
     where
-        F: Fn(&i64, &i64) -> bool,
-        H: Fn(&Option<String>, &Option<String>) -> bool,
+        F: Fn(Ordering) -> bool,
     {
-        if a.is_none() {
-            return Self::from(str_cmp(&a, &b));
-        }
-
-        let ar = a.as_ref().unwrap();
-        let ir = ar.parse::<i64>();
-        if ir.is_err() {
-            return Self::from(str_cmp(&a, &b));
-        }
-
-        if b.is_none() {
-            return Self::from(str_cmp(&a, &b));
+        match (a, b) {
+            (Some(a), Some(b)) => Self::from(a.loose_cmp(&b).is_some_and(ord_cmp)),
+            // an unset value is always less than a set value
+            (a, b) => Self::from(ord_cmp(a.is_some().cmp(&b.is_some()))),
         }
+    }
 
-        let br = b.as_ref().unwrap();
-        let jr = br.parse::<i64>();
-        if jr.is_err() {
-            return Self::from(str_cmp(&a, &b));
+    pub(crate) fn eq(a: Option<Value>, b: Option<Value>) -> Self {
+        match (a, b) {
+            (Some(a), Some(b)) => Self::from(a.loose_eq(&b)),
+            (a, b) => Self::from(a.is_none() && b.is_none()),
         }
+    }
 
-        let i = ir.unwrap();
-        let j = jr.unwrap();
-
-        Self::from(num_cmp(&i, &j))
+    pub(crate) fn ne(a: Option<Value>, b: Option<Value>) -> Self {
+        Self::from(!Self::eq(a, b).as_evaluation())
     }
 
-    pub(crate) fn gt(a: Option<String>, b: Option<String>) -> Self {
-        Self::try_num(a, b, i64::gt, Option::<String>::gt)
+    pub(crate) fn gt(a: Option<Value>, b: Option<Value>) -> Self {
+        Self::try_cmp(a, b, Ordering::is_gt)
     }
 
-    pub(crate) fn ge(a: Option<String>, b: Option<String>) -> Self {
-        Self::try_num(a, b, i64::ge, Option::<String>::ge)
+    pub(crate) fn ge(a: Option<Value>, b: Option<Value>) -> Self {
+        Self::try_cmp(a, b, Ordering::is_ge)
     }
 
-    pub(crate) fn lt(a: Option<String>, b: Option<String>) -> Self {
-        Self::try_num(a, b, i64::lt, Option::<String>::lt)
+    pub(crate) fn lt(a: Option<Value>, b: Option<Value>) -> Self {
+        Self::try_cmp(a, b, Ordering::is_lt)
     }
 
-    pub(crate) fn le(a: Option<String>, b: Option<String>) -> Self {
-        Self::try_num(a, b, i64::le, Option::<String>::le)
+    pub(crate) fn le(a: Option<Value>, b: Option<Value>) -> Self {
+        Self::try_cmp(a, b, Ordering::is_le)
     }
 }
 
@@ -204,6 +201,9 @@ where
     }
 
     fn parse(&mut self) -> StepResult<Condition> {
+        // set by a leading "!" and applied to the next evaluated condition
+        let mut negate = false;
+
         loop {
             let c = match self.current()? {
                 Some(c) => c,
@@ -213,6 +213,12 @@ where
             let tagname = self.tagname.to_owned();
 
             match c {
+                '!' => {
+                    self.input_mut().into_step()?.step().into_step()?;
+                    self.bypass_whitespace()?;
+                    negate = !negate;
+                    continue;
+                },
                 '(' => {
                     self.input_mut().into_step()?.step().into_step()?;
 
@@ -267,6 +273,10 @@ where
                 _ => {
                     self.bypass_whitespace()?;
 
+                    if let Some('!') = self.current()? {
+                        continue;
+                    }
+
                     let left_value = self.parse_value(&tagname)?;
 
                     self.bypass_whitespace()?;
@@ -283,7 +293,7 @@ where
                                     let right_value = self.parse_value(&tagname)?;
                                     self.condition = Some(match self.bypass.as_ref() {
                                         Some(b) => Condition::from(*b),
-                                        None => Condition::from(left_value == right_value),
+                                        None => Condition::eq(left_value, right_value),
                                     });
                                 },
                                 _ => return self.tag_unexpected_char_expected(&tagname, "=")?,
@@ -300,7 +310,7 @@ where
                                     let right_value = self.parse_value(&tagname)?;
                                     self.condition = Some(match self.bypass.as_ref() {
                                         Some(b) => Condition::from(*b),
-                                        None => Condition::from(left_value != right_value),
+                                        None => Condition::ne(left_value, right_value),
                                     });
                                 },
                                 _ => return self.tag_unexpected_char_expected(&tagname, "="),
@@ -361,13 +371,22 @@ where
                         _ => {
                             self.condition = Some(match self.bypass.as_ref() {
                                 Some(b) => Condition::from(*b),
-                                None => Condition::from(Variable::value_is_truthy(left_value.clone())),
+                                None => Condition::from(Variable::value_is_truthy(left_value.as_ref())),
                             });
                         },
                     }
                 },
             }
 
+            if negate {
+                negate = false;
+
+                // a bypassed condition carries the short-circuited result
+                if self.bypass.is_none() {
+                    self.condition_mut().into_step()?.negate();
+                }
+            }
+
             self.bypass_whitespace()?;
             match self.tag_current_or_unexpected_eof(&tagname)? {
                 '&' => {
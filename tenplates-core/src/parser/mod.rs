@@ -6,24 +6,32 @@ pub(crate) mod steps;
 
 use {
     crate::{
-        context::Context,
+        context::{ Arg, Context, Function, },
+        data::{ self, Format, },
         error::{
             InternalError,
             InternalResult,
             IntoInternal,
             OrElseUpgrade,
         },
+        filter::{ Escape, Filter, },
         input::{ Input, TryIntoInput },
+        listing::{ self, Entry, Listing, Sort, },
+        macros::*,
         parser::{
-            if_parser::IfParser,
+            if_parser::{ Condition, IfParser, },
             steps::*,
         },
         output::Output,
+        value::{ Number, ToOutput, Value, },
     },
+    rusqlite::{ Connection, OpenFlags, },
     std::{
+        cell::RefCell,
+        collections::{ HashMap, VecDeque, },
         fmt::Debug,
-        fs::{ canonicalize, File, OpenOptions },
-        io::{ Read, self, Write, },
+        fs::{ canonicalize, File, OpenOptions, read_to_string, },
+        io::{ self, BufRead, BufReader, Cursor, Lines, Read, Write, },
         path::PathBuf,
     },
 };
@@ -31,19 +39,28 @@ use {
 #[derive(Clone, Debug)]
 pub(crate) enum ParseUntil {
     EndAdd,
+    EndBlock,
     EndDiv,
     EndFn,
     EndForeach,
     EndFordir,
     EndForfile,
+    EndForkeys,
+    EndForline,
+    EndForrange,
     EndForsplit,
     EndIf,
+    EndInvoke,
+    EndMatch,
     EndMod,
     EndMul,
     EndNth,
     EndPow,
+    EndQuery,
+    EndRaw,
     EndSet,
     EndSub,
+    EndWith,
     Eof,
     // used exclusively by if tag
     ConditionEnd,
@@ -53,20 +70,35 @@ pub(crate) enum ParseUntil {
 #[derive(Clone, Debug)]
 pub(crate) enum EndPosition {
     Add,
+    Block,
+    Break,
+    Case,
+    Continue,
+    Default,
+    Elif,
     Else,
     Div,
     Fn,
     Foreach,
     Fordir,
     Forfile,
+    Forkeys,
+    Forline,
+    Forrange,
     Forsplit,
     Nth,
     If,
+    Invoke,
+    Match,
     Mod,
     Mul,
     Pow,
+    Query,
+    Raw,
+    Return,
     Set,
     Sub,
+    With,
     Eof,
 }
 
@@ -91,6 +123,223 @@ where
     fn take_output(&mut self) -> InternalResult<Output<W>>;
 }
 
+/// The elements of a loop, which may be computed only as they are reached.
+trait LoopValues {
+    fn len(&self) -> usize;
+
+    fn get(&self, index: usize) -> InternalResult<Option<Value>>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl LoopValues for Vec<Value> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn get(&self, index: usize) -> InternalResult<Option<Value>> {
+        Ok(self.as_slice().get(index).cloned())
+    }
+}
+
+/// The integers of a `forrange` loop, computed from their index so that a
+/// large range is never held in memory.
+struct RangeValues {
+    start: i64,
+    step: i64,
+    len: usize,
+    reversed: bool,
+}
+
+impl RangeValues {
+    fn new(start: i64, end: i64, step: i64, reversed: bool) -> InternalResult<Self> {
+        // the end is inclusive, a start beyond the end produces no values
+        let len = if start > end {
+            0
+        }
+        else {
+            (i128::from(end) - i128::from(start)) / i128::from(step) + 1
+        };
+
+        // the size of the loop must fit in its loop context
+        let len = i64::try_from(len).ok()
+            .and_then(|len| usize::try_from(len).ok())
+            .into_internal(format!("The range '{start}' to '{end}' of a 'forrange' tag is too large"))?;
+
+        Ok(Self { start, step, len, reversed, })
+    }
+}
+
+impl LoopValues for RangeValues {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> InternalResult<Option<Value>> {
+        if index >= self.len {
+            return Ok(None);
+        }
+
+        let index = if self.reversed { self.len - index - 1 } else { index };
+        let value = i128::from(self.start) + index as i128 * i128::from(self.step);
+
+        Ok(Some(Value::from(value as i64)))
+    }
+}
+
+/// Where the lines of a `forline` loop are read from, along with the lines
+/// which are skipped.
+struct LineSource {
+    // a file, or the text of a variable when there is no path
+    path: Option<PathBuf>,
+    text: String,
+    skip_blank: bool,
+    comment: Option<String>,
+}
+
+impl LineSource {
+    /// Reads the lines of the source from the start.
+    fn read(&self) -> InternalResult<LineReader<'_>> {
+        let reader: Box<dyn BufRead> = match self.path.as_ref() {
+            Some(path) => Box::new(BufReader::new(File::open(path)
+                .into_internal(format!("Failed to open file {path:?}"))?)),
+            None => Box::new(Cursor::new(self.text.as_bytes())),
+        };
+
+        Ok(LineReader { source: self, lines: reader.lines(), })
+    }
+}
+
+/// The lines of a [LineSource] which are not skipped.
+struct LineReader<'source> {
+    source: &'source LineSource,
+    lines: Lines<Box<dyn BufRead + 'source>>,
+}
+
+impl Iterator for LineReader<'_> {
+    type Item = InternalResult<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(InternalError::new(format!(
+                    "Failed to read line in 'forline' tag: {e}"
+                )))),
+            };
+
+            if self.source.skip_blank && line.trim().is_empty() {
+                continue;
+            }
+
+            if self.source.comment.as_ref().is_some_and(|prefix| !prefix.is_empty() && line.trim_start().starts_with(prefix)) {
+                continue;
+            }
+
+            return Some(Ok(line));
+        }
+    }
+}
+
+/// The lines of a `forline` loop. The lines are counted up front, then read
+/// again only as they are reached, keeping those around the current line for
+/// the loop context.
+struct LineValues<'source> {
+    len: usize,
+    reader: RefCell<LineReader<'source>>,
+    // the most recently read lines by index, and the index of the next
+    read: RefCell<(VecDeque<(usize, Value)>, usize)>,
+}
+
+impl<'source> LineValues<'source> {
+    fn new(source: &'source LineSource) -> InternalResult<Self> {
+        let mut len = 0;
+        for line in source.read()? {
+            line?;
+            len += 1;
+        }
+
+        Ok(Self { len, reader: RefCell::new(source.read()?), read: RefCell::default(), })
+    }
+}
+
+impl LoopValues for LineValues<'_> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> InternalResult<Option<Value>> {
+        if index >= self.len {
+            return Ok(None);
+        }
+
+        let (lines, next) = &mut *self.read.borrow_mut();
+
+        while *next <= index {
+            // the source may have changed since its lines were counted
+            let Some(line) = self.reader.borrow_mut().next().transpose()? else {
+                return Ok(None);
+            };
+
+            lines.push_back((*next, Value::from(line)));
+            *next += 1;
+
+            // the previous, current and next lines
+            if lines.len() > 3 {
+                lines.pop_front();
+            }
+        }
+
+        Ok(lines.iter().find(|(i, _)| *i == index).map(|(_, line)| line.to_owned()))
+    }
+}
+
+/// The variables named by a loop tag, i.e. `k`, `v` and `l` in
+/// `{% forkeys k, v in obj as l %}`.
+struct LoopVariables {
+    /// Holds the value paired with each element, i.e. its member name in a
+    /// `forkeys` loop.
+    key: Option<String>,
+    element: String,
+    loop_variable: Option<String>,
+    /// Holds the front matter of the file at each element, i.e. `item.meta`
+    /// in a `forfile` loop.
+    meta: Option<String>,
+}
+
+impl LoopVariables {
+    fn new(element: String, loop_variable: Option<String>) -> Self {
+        Self { key: None, element, loop_variable, meta: None, }
+    }
+}
+
+/// An iteration of the innermost loop being parsed.
+#[derive(Clone, Debug)]
+pub(crate) struct LoopState {
+    index: usize,
+    variable: Option<String>,
+}
+
+/// The content of a `block` tag along with the path of the template it was
+/// found in.
+#[derive(Clone, Debug)]
+pub(crate) struct Block {
+    content: Vec<u8>,
+    path: PathBuf,
+}
+
+/// A block being rendered, used by the `super` tag.
+#[derive(Clone, Debug)]
+pub(crate) struct BlockState {
+    name: String,
+    /// The overrides of the block from the most derived template down to the
+    /// content of the block in the outermost template.
+    blocks: Vec<Block>,
+    index: usize,
+}
+
 #[derive(Debug)]
 pub struct TemplateParser<R, W>
 where
@@ -104,6 +353,19 @@ where
     bypass: bool,
     output: Option<Output<W>>,
     end_position: Option<EndPosition>,
+    // the innermost loop, used by the 'break', 'continue' and 'cycle' tags
+    current_loop: Option<LoopState>,
+    // whether 'block' tags define overrides rather than render, i.e. after an
+    // 'extend' tag
+    extending: bool,
+    // the overrides defined by extending templates, by block name
+    blocks: HashMap<String, Vec<Block>>,
+    // the innermost block being rendered, used by the 'super' tag
+    current_block: Option<BlockState>,
+    // whether a 'return' tag may be used, i.e. within a function body
+    in_function: bool,
+    // the value of the 'return' tag which stopped this parser
+    returned: Option<Value>,
 }
 
 impl<R, W> Parser<R, W> for TemplateParser<R, W>
@@ -182,6 +444,7 @@ where
 
     pub(crate) fn set_extend(&mut self, path: PathBuf) {
         self.extend = Some(path);
+        self.extending = true;
     }
 
     fn new_internal<I, O>(
@@ -202,6 +465,12 @@ where
             bypass,
             output: Some(into_output.into()),
             end_position: None,
+            current_loop: None,
+            extending: false,
+            blocks: HashMap::new(),
+            current_block: None,
+            in_function: false,
+            returned: None,
         })
     }
 
@@ -212,16 +481,21 @@ where
         I: TryIntoInput<R>,
         O: Into<Output<W>>,
     {
-        Self::new_internal(
+        let mut parser = Self::new_internal(
             context, into_input, into_output,
             ParseUntil::Eof, false,
-        )
+        )?;
+
+        let path = parser.input()?.path().to_owned();
+        parser.context_mut()?.default_escape(path);
+
+        Ok(parser)
     }
 
     pub(crate) fn spawn_sealed(&mut self, path: PathBuf) -> InternalResult<TemplateParser<File, W>> {
         TemplateParser::new_internal(
             self.context()?.clone(),
-            path,
+            Input::template(path)?,
             self.take_output()?,
             ParseUntil::Eof,
             self.bypass,
@@ -247,7 +521,7 @@ where
 
         TemplateParser::new_internal(
             self.take_context()?,
-            file,
+            Input::template(file)?,
             self.take_output()?,
             ParseUntil::Eof,
             self.bypass,
@@ -275,15 +549,18 @@ where
             .into_internal("Invalid utf8 in content")?;
 
         let input_path = self.input()?.path().to_owned();
-        self.context_mut()?.add_variable("CONTENT", input_path, content);
+        self.context_mut()?.add_trusted_variable("CONTENT", input_path, content);
 
-        TemplateParser::new_internal(
+        let mut extend = TemplateParser::new_internal(
             self.take_context()?,
-            extend,
+            Input::template(extend)?,
             self.take_output()?,
             ParseUntil::Eof,
             self.bypass,
-        )
+        )?;
+        extend.blocks = std::mem::take(&mut self.blocks);
+
+        Ok(extend)
     }
 
     pub(crate) fn consume_extend(&mut self, mut extend: TemplateParser<File, W>) -> InternalResult<()> {
@@ -334,18 +611,90 @@ where
     pub(crate) fn spawn_limited<'limited>(
         &mut self, input: Input<&'limited [u8]>, parse_until: ParseUntil
     ) -> InternalResult<TemplateParser<&'limited [u8], W>> {
-        TemplateParser::new_internal(
+        let mut limited = TemplateParser::new_internal(
             self.take_context()?,
             input,
             self.take_output()?,
             parse_until,
             self.bypass,
-        )
+        )?;
+        limited.current_loop = self.current_loop.clone();
+        limited.extending = self.extending;
+        limited.blocks = std::mem::take(&mut self.blocks);
+        limited.current_block = self.current_block.clone();
+        limited.in_function = self.in_function;
+
+        Ok(limited)
     }
 
     pub(crate) fn consume_limited(&mut self, mut limited: TemplateParser<&[u8], W>) -> InternalResult<()> {
         self.give_context(Some(limited.take_context()?));
         self.give_output(Some(limited.take_output()?));
+        self.blocks = std::mem::take(&mut limited.blocks);
+
+        // hand a 'break' or 'continue' up to the enclosing loop, and a
+        // 'return' up to the enclosing function
+        if let Some(pos @ (EndPosition::Break|EndPosition::Continue|EndPosition::Return)) = limited.end_position.take() {
+            self.set_end_position(pos);
+            self.returned = limited.returned.take();
+        }
+
+        Ok(())
+    }
+
+    /// Parses one iteration of a loop's content, returning the
+    /// [flow](FlowControl) of a `break` or `continue` tag reached within it.
+    pub(crate) fn parse_loop_body(
+        &mut self, content: &[u8], parse_until: ParseUntil, state: LoopState,
+    ) -> InternalResult<Option<FlowControl>> {
+        let mut input = content.try_into_input()?;
+        input.set_path(self.input()?.path());
+
+        let mut limited = self.spawn_limited(input, parse_until)?;
+        limited.current_loop = Some(state);
+        limited.parse_internal()?;
+
+        let flow = match limited.end_position.take() {
+            Some(EndPosition::Break) => Some(FlowControl::Break),
+            Some(EndPosition::Continue) => Some(FlowControl::Continue),
+            // a 'return' also stops the loop, which then hands it up
+            Some(pos @ EndPosition::Return) => {
+                self.set_end_position(pos);
+                self.returned = limited.returned.take();
+
+                Some(FlowControl::Break)
+            },
+            _ => None,
+        };
+
+        self.consume_limited(limited)?;
+
+        Ok(flow)
+    }
+
+    /// Stops this parser when a `break`, `continue` or `return` tag was
+    /// reached within a block, leaving it to the enclosing loop or function.
+    fn propagate_loop_flow(&self) -> StepResult<()> {
+        match self.end_position {
+            Some(EndPosition::Break|EndPosition::Continue|EndPosition::Return) => Err(Ok(FlowControl::Break)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Renders the override of a block at `state.index`, within which a
+    /// `super` tag renders the next.
+    pub(crate) fn parse_block(&mut self, state: BlockState) -> InternalResult<()> {
+        let Block { content, path, } = state.blocks.get(state.index)
+            .into_internal(format!("Block '{}' has no override at {}", state.name, state.index))?
+            .to_owned();
+
+        let mut input = content.as_slice().try_into_input()?;
+        input.set_path(path);
+
+        let mut limited = self.spawn_limited(input, ParseUntil::EndBlock)?;
+        limited.current_block = Some(state);
+        limited.parse_internal()?;
+        self.consume_limited(limited)?;
 
         Ok(())
     }
@@ -373,13 +722,16 @@ where
     pub(crate) fn spawn_limited_sealed<'limited>(
         &mut self, context: Context, input: Input<&'limited [u8]>, parse_until: ParseUntil
     ) -> InternalResult<TemplateParser<&'limited [u8], W>> {
-        TemplateParser::new_internal(
+        let mut limited = TemplateParser::new_internal(
             context,
             input,
             self.take_output()?,
             parse_until,
             self.bypass,
-        )
+        )?;
+        limited.in_function = true;
+
+        Ok(limited)
     }
 
     pub(crate) fn consume_limited_sealed(&mut self, mut limited: TemplateParser<&[u8], W>) -> InternalResult<()> {
@@ -405,347 +757,553 @@ where
         Ok(())
     }
 
+    /// The function `name` along with the sealed context it is called in,
+    /// where `content` is the body of an `invoke` tag, exposed as `CONTENT`.
+    fn function_context(
+        &self, name: &str, args: Vec<Arg>, content: Option<String>,
+    ) -> InternalResult<(Function, Context)> {
+        let function = self.context()?.function(name)
+            .into_internal(format!("Function '{name}' never defined"))?
+            .to_owned();
+
+        let values = function.bind(name, args)?;
+        let path = self.input()?.path().to_owned();
+        let mut ctx = self.context()?.to_owned();
+
+        for (named, value) in values {
+            // a parameter masks the variables beneath it in the caller, i.e.
+            // `user.name` for `user`
+            ctx.unset_variable(&named);
+
+            if let Some(value) = value {
+                ctx.add_variable(named, &path, value);
+            }
+        }
+
+        if let Some(content) = content {
+            ctx.add_trusted_variable("CONTENT", &path, content);
+        }
+
+        Ok((function, ctx))
+    }
+
+    /// Calls the function `name` with `args`, writing its output.
+    pub(crate) fn parse_function(
+        &mut self, name: &str, args: Vec<Arg>, content: Option<String>,
+    ) -> InternalResult<()> {
+        let (function, ctx) = self.function_context(name, args, content)?;
+
+        // make sure we write all buffered content before spawning the sealed
+        // parser
+        self.output_mut()?.flush_buffer_to_content();
+        self.parse_limited_sealed(ctx, function.as_bytes(), ParseUntil::EndFn)
+    }
+
+    /// Calls the function `name` with `args` into its own output, returning
+    /// the output along with the value of the `return` tag reached within it.
+    fn run_function(&mut self, name: &str, args: Vec<Arg>) -> InternalResult<(String, Option<Value>)> {
+        let (function, ctx) = self.function_context(name, args, None)?;
+
+        let mut input = function.as_bytes().try_into_input()?;
+        input.set_path(self.input()?.path());
+
+        let mut output_bytes = Vec::new();
+        let mut sealed = TemplateParser::new_internal(
+            ctx,
+            input,
+            &mut output_bytes,
+            ParseUntil::EndFn,
+            self.bypass,
+        )?;
+        sealed.in_function = true;
+        sealed.parse_internal()?;
+        sealed.write()?;
+        let returned = sealed.returned.take();
+        drop(sealed);
+
+        let output_string = String::from_utf8(output_bytes)
+            .into_internal(format!("Invalid utf-8 in output of function '{name}'"))?;
+
+        Ok((output_string, returned))
+    }
+
+    /// Calls the function `name` with `args`, returning its output rather
+    /// than writing it.
+    pub(crate) fn render_function(&mut self, name: &str, args: Vec<Arg>) -> InternalResult<String> {
+        Ok(self.run_function(name, args)?.0)
+    }
+
+    /// Calls the function `name` with `args`, discarding its output, and
+    /// returns the value of the `return` tag reached within it.
+    pub(crate) fn call_function(&mut self, name: &str, args: Vec<Arg>) -> InternalResult<Option<Value>> {
+        Ok(self.run_function(name, args)?.1)
+    }
+
     pub(crate) fn spawn_child<O2: Write + Debug, W2: Into<Output<O2>>>(
         &mut self, output: W2, parse_until: ParseUntil
     ) -> InternalResult<TemplateParser<R, O2>> {
-        TemplateParser::new_internal(
+        let mut child = TemplateParser::new_internal(
             self.take_context()?,
             self.take_input()?,
             output,
             parse_until,
             self.bypass,
-        )
+        )?;
+        child.extending = self.extending;
+        child.blocks = std::mem::take(&mut self.blocks);
+        child.current_block = self.current_block.clone();
+        child.current_loop = self.current_loop.clone();
+        child.in_function = self.in_function;
+
+        Ok(child)
     }
 
     pub(crate) fn consume_child<O2: Write + Debug>(&mut self, mut child: TemplateParser<R, O2>) -> InternalResult<()> {
         self.give_context(Some(child.take_context()?));
         self.give_input(Some(child.take_input()?));
+        self.blocks = std::mem::take(&mut child.blocks);
+
+        // hand a 'break' or 'continue' up to the enclosing loop, and a
+        // 'return' up to the enclosing function
+        if let Some(pos @ (EndPosition::Break|EndPosition::Continue|EndPosition::Return)) = child.end_position.take() {
+            self.set_end_position(pos);
+            self.returned = child.returned.take();
+        }
 
         Ok(())
     }
 
-    pub(crate) fn parse_child(&mut self, parse_until: ParseUntil) -> InternalResult<String> {
+    /// Compiles the body of a tag into a value. The body is not escaped as
+    /// the value is escaped when it is output.
+    pub(crate) fn parse_child(&mut self, parse_until: ParseUntil) -> StepResult<String> {
+        let escape = self.context().into_step()?.escape();
+        self.context_mut().into_step()?.set_escape(Escape::None);
+
+        let content = self.render_child(parse_until);
+
+        if let Ok(context) = self.context_mut() {
+            context.set_escape(escape);
+        }
+
+        content
+    }
+
+    /// Compiles the body of a tag as it would be written to the output. A
+    /// `break`, `continue` or `return` tag reached within the body stops the
+    /// tag, leaving it to the enclosing loop or function.
+    pub(crate) fn render_child(&mut self, parse_until: ParseUntil) -> StepResult<String> {
         let mut output_bytes = Vec::new();
-        let mut child = self.spawn_child(&mut output_bytes, parse_until)?;
-        child.parse_internal()?;
-        child.write()?;
-        self.consume_child(child)?;
+        let mut child = self.spawn_child(&mut output_bytes, parse_until).into_step()?;
+        child.parse_internal().into_step()?;
+        child.write().into_step()?;
+        self.consume_child(child).into_step()?;
+        self.propagate_loop_flow()?;
 
         let output_string = String::from_utf8(output_bytes)
-            .into_internal("Invalid utf-8 found in output")?;
+            .into_internal("Invalid utf-8 found in output")
+            .into_step()?;
 
         Ok(output_string)
     }
 
-    fn parse_add(&mut self) -> StepResult<()> {
-        if self.bypass() {
-            if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
-            }
-
-            self.buffer_all_until_end_of_tag("add")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndAdd)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+    pub(crate) fn parse_child_as_number(&mut self, parse_until: ParseUntil) -> StepResult<Number> {
+        let content = self.parse_child(parse_until)?;
 
-            match end_position {
-                EndPosition::Add => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'add' tag, '{pos:?}'"
-                )))),
-            };
+        Number::parse(&content)
+            .into_internal(format!("Failed to parse content '{}' as a number", content.trim()))
+            .into_step()
+    }
 
-            self.output_mut().into_step()?.flush_buffer_to_content();
+    /// Adds the [loop context](LoopState) of an iteration to context,
+    /// returning the names of the added variables.
+    fn add_loop_variables(
+        &mut self, loop_variable: &str, parent: Option<&str>, values: &impl LoopValues, index: usize,
+    ) -> InternalResult<Vec<String>> {
+        let size = values.len();
+        let mut variables = vec![
+            (String::from("index"), Value::from(index as i64)),
+            (String::from("index1"), Value::from(index as i64 + 1)),
+            (String::from("revindex"), Value::from((size - index - 1) as i64)),
+            (String::from("size"), Value::from(size as i64)),
+            (String::from("isfirst"), Value::from(i64::from(index == 0))),
+            (String::from("islast"), Value::from(i64::from(index + 1 == size))),
+        ];
 
-            Ok(())
+        if let Some(prev) = index.checked_sub(1).map(|i| values.get(i)).transpose()?.flatten() {
+            variables.push((String::from("prev"), prev));
         }
-        else {
-            if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
-            }
-
-            self.output_mut().into_step()?.clear_buffer();
 
-            let value = self.parse_value_as_number("add")?;
+        if let Some(next) = values.get(index + 1)? {
+            variables.push((String::from("next"), next));
+        }
 
-            self.expect_end_of_tag("add")?;
+        // the parent's loop context, including its own parent
+        if let Some(parent) = parent {
+            let prefix = format!("{parent}.");
 
-            let content = self.parse_child(ParseUntil::EndAdd).into_step()?
-                .trim()
-                .parse::<i64>()
-                .into_internal("Failed to parse content as a number")
-                .into_step()?;
+            for (name, value) in self.context()?.members(parent) {
+                variables.push((format!("parent.{}", &name[prefix.len()..]), value));
+            }
+        }
 
-            self.output_mut().into_step()?.write_str(&(value + content).to_string());
-            self.output_mut().into_step()?.flush_buffer_to_content();
+        let path = self.input()?.path().to_owned();
+        let mut names = Vec::with_capacity(variables.len());
 
-            Ok(())
+        for (name, value) in variables {
+            let name = format!("{loop_variable}.{name}");
+            self.context_mut()?.add_variable(&name, &path, value);
+            names.push(name);
         }
-    }
 
-    fn parse_ad(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
-                self.push_step()?;
-                self.parse_add()
-            },
-            _ => self.unexpected_tag(),
-        }
+        Ok(names)
     }
 
-    fn parse_assert(&mut self) -> StepResult<()> {
-        if self.bypass() {
-            if !self.buffer_whitespace_enforce_one()? {
-                return self.unknown_tag();
-            }
+    /// Parses the options following the path of a `fordir` or `forfile`
+    /// tag up to the end of the tag, returning the loop variable when one is
+    /// named.
+    fn parse_listing(&mut self, tagname: &str, listing: &mut Listing) -> StepResult<Option<String>> {
+        let mut loop_variable = None;
 
-            self.buffer_all_until_end_of_self_closing_tag("assert")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
+        loop {
+            self.bypass_whitespace()?;
 
-            Ok(())
-        }
-        else {
-            if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
+            if self.tag_current_or_unexpected_eof(tagname)? == '%' {
+                break;
             }
 
-            self.output_mut().into_step()?.clear_buffer();
+            let option = self.parse_variable_name(tagname)?;
 
-            let condition = IfParser::parse_result("assert", self)?;
+            match option.as_str() {
+                "as" => {
+                    loop_variable = Some(self.parse_variable_name(tagname)?);
+                },
+                "depth" => {
+                    self.bypass_whitespace()?;
 
-            self.output_mut().into_step()?.clear_buffer();
-            self.bypass_whitespace()?;
-            self.expect_end_of_self_close_tag("assert")?;
+                    let depth = match self.parse_value_as_number(tagname)? {
+                        Number::Integer(depth) if depth > 0 => depth as usize,
+                        _ => return Err(Err(InternalError::new(format!(
+                            "The depth of a '{tagname}' tag must be a positive integer"
+                        )))),
+                    };
 
-            if !condition.as_evaluation() {
-                return Err(Err(InternalError::new(
-                    "ASSERTION FAILED"
-                )));
-            }
+                    listing.depth(depth);
+                },
+                "excluding" => {
+                    self.bypass_whitespace()?;
 
-            Ok(())
-        }
-    }
+                    let glob = self.parse_value_as_string(tagname)?
+                        .into_internal(format!("The excluded pattern of a '{tagname}' tag was None"))
+                        .into_step()?;
 
-    fn parse_asser(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            't' => {
-                self.push_step()?;
-                self.parse_assert()
-            },
-            _ => self.unexpected_tag(),
-        }
-    }
+                    listing.excluding(&glob).into_step()?;
+                },
+                "ext" => {
+                    self.bypass_whitespace()?;
 
-    fn parse_asse(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'r' => {
-                self.push_step()?;
-                self.parse_asser()
-            },
-            _ => self.unexpected_tag(),
+                    let ext = self.parse_value_as_string(tagname)?
+                        .into_internal(format!("The extension of a '{tagname}' tag was None"))
+                        .into_step()?;
+
+                    listing.extension(&ext);
+                },
+                "limit" => {
+                    self.bypass_whitespace()?;
+
+                    let limit = self.parse_listing_count(tagname, "limit")?;
+                    listing.limit(limit);
+                },
+                "matching" => {
+                    self.bypass_whitespace()?;
+
+                    let glob = self.parse_value_as_string(tagname)?
+                        .into_internal(format!("The matched pattern of a '{tagname}' tag was None"))
+                        .into_step()?;
+
+                    listing.matching(&glob).into_step()?;
+                },
+                "offset" => {
+                    self.bypass_whitespace()?;
+
+                    let offset = self.parse_listing_count(tagname, "offset")?;
+                    listing.offset(offset);
+                },
+                "recursive" => listing.recursive(),
+                "reversed" => listing.reversed(),
+                "skiphidden" => listing.skip_hidden(),
+                "sort" => {
+                    if !self.bypass_whitespace_enforce_one()? {
+                        return Err(Err(InternalError::new(format!("Unexpected character in '{tagname}' tag"))));
+                    }
+
+                    self.tag_expect_char(tagname, |c| matches!(c, 'b'))?;
+                    self.tag_expect_char(tagname, |c| matches!(c, 'y'))?;
+
+                    if !self.bypass_whitespace_enforce_one()? {
+                        return Err(Err(InternalError::new(format!("Unexpected character in '{tagname}' tag"))));
+                    }
+
+                    let sort = self.parse_variable_name(tagname)?
+                        .parse::<Sort>()
+                        .into_step()?;
+
+                    listing.sort(sort);
+                },
+                _ => return Err(Err(InternalError::new(format!(
+                    "Unknown option '{option}' in '{tagname}' tag"
+                )))),
+            }
         }
+
+        self.expect_end_of_tag(tagname)?;
+
+        Ok(loop_variable)
     }
 
-    fn parse_ass(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'e' => {
-                self.push_step()?;
-                self.parse_asse()
-            },
-            _ => self.unexpected_tag(),
+    /// Parses a count of entries for the `option` of a `fordir` or `forfile`
+    /// tag, i.e. the `10` in `limit 10`.
+    fn parse_listing_count(&mut self, tagname: &str, option: &str) -> StepResult<usize> {
+        match self.parse_value_as_number(tagname)? {
+            Number::Integer(count) if count >= 0 => Ok(count as usize),
+            _ => Err(Err(InternalError::new(format!(
+                "The {option} of a '{tagname}' tag must be a non-negative integer"
+            )))),
         }
     }
 
-    fn parse_as(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            's' => {
-                self.push_step()?;
-                self.parse_ass()
-            },
-            _ => self.unexpected_tag(),
+    /// Parses the content of a loop tag once for each value, or the else
+    /// content when there are no values. Each value's key is stored as well
+    /// when the loop names a key variable.
+    fn parse_loop(
+        &mut self, parse_until: ParseUntil, variables: LoopVariables, values: impl LoopValues,
+        keys: Vec<Value>, content: &[u8], else_content: Option<Vec<u8>>,
+    ) -> StepResult<()> {
+        if values.is_empty() {
+            if let Some(content) = else_content {
+                self.parse_limited(content.as_slice(), parse_until)
+                    .into_step()?;
+                self.propagate_loop_flow()?;
+            }
+
+            return Ok(());
         }
-    }
 
-    fn parse_a(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
-                self.push_step()?;
-                self.parse_ad()
-            },
-            's' => {
-                self.push_step()?;
-                self.parse_as()
-            },
-            _ => self.unexpected_tag(),
+        let LoopVariables { key, element, loop_variable, meta, } = variables;
+        let parent = self.current_loop.as_ref()
+            .and_then(|state| state.variable.to_owned());
+
+        let path = self.input().into_step()?.path().to_owned();
+        for index in 0..values.len() {
+            let value = values.get(index).into_step()?.unwrap_or_default();
+
+            // front matter is only read once its file is reached
+            let front_matter = match meta.as_ref() {
+                Some(meta) => Some((meta, value.to_output(&[]).and_then(data::front_matter).into_step()?)),
+                None => None,
+            };
+
+            self.context_mut().into_step()?.add_variable(&element, &path, value);
+
+            if let (Some(key), Some(key_value)) = (key.as_ref(), keys.as_slice().get(index)) {
+                self.context_mut().into_step()?.add_variable(key, &path, key_value.to_owned());
+            }
+
+            if let Some((meta, front_matter)) = front_matter.as_ref() {
+                self.context_mut().into_step()?.add_variable(meta, &path, front_matter.to_owned());
+            }
+
+            let loop_variables = match loop_variable.as_deref() {
+                Some(loop_variable) => self.add_loop_variables(
+                    loop_variable, parent.as_deref(), &values, index,
+                ).into_step()?,
+                None => Vec::new(),
+            };
+
+            let state = LoopState { index, variable: loop_variable.to_owned(), };
+            let flow = self.parse_loop_body(content, parse_until.clone(), state)
+                .into_step()?;
+
+            self.context_mut().into_step()?.pop_variable(&element);
+
+            if let Some(key) = key.as_ref() {
+                self.context_mut().into_step()?.pop_variable(key);
+            }
+
+            if let Some((meta, _)) = front_matter {
+                self.context_mut().into_step()?.pop_variable(meta);
+            }
+
+            for name in loop_variables {
+                self.context_mut().into_step()?.pop_variable(name);
+            }
+
+            if let Some(FlowControl::Break) = flow {
+                break;
+            }
         }
+
+        self.propagate_loop_flow()
     }
 
-    fn parse_call(&mut self) -> StepResult<()> {
+    fn parse_add(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unexpected_tag();
             }
 
-            self.buffer_all_until_end_of_self_closing_tag("call")?;
+            self.buffer_all_until_end_of_tag("add")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndAdd)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Add => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'add' tag, '{pos:?}'"
+                )))),
+            };
+
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
         else {
-            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
                 return self.unknown_tag();
             }
 
-            let path = self.parse_value_as_path("call")?
-                .into_internal("Path was None and this message should be better")
-                .into_step()?;
-            self.expect_end_of_self_close_tag("call")?;
+            self.output_mut().into_step()?.clear_buffer();
 
-            // make sure we write all buffered content before spawning the sealed
-            // parser
+            let value = self.parse_value_as_number("add")?;
+
+            self.expect_end_of_tag("add")?;
+
+            let content = self.parse_child_as_number(ParseUntil::EndAdd)?;
+
+            self.output_mut().into_step()?.write_str(&value.add(content).into_step()?.to_string());
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.parse_unsealed(path).into_step()?;
 
             Ok(())
         }
     }
 
-    fn parse_cal(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
-                self.push_step()?;
-                self.parse_call()
-            },
-            _ => self.unexpected_tag(),
-        }
-    }
-
-    fn parse_ca(&mut self) -> StepResult<()> {
+    fn parse_ad(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            'd' => {
                 self.push_step()?;
-                self.parse_cal()
+                self.parse_add()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_compile(&mut self) -> StepResult<()> {
+    fn parse_assert(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_tag();
             }
 
-            self.buffer_all_until_end_of_self_closing_tag("compile")?;
+            self.buffer_all_until_end_of_self_closing_tag("assert")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
         else {
-            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
                 return self.unknown_tag();
             }
 
-            let path = self.parse_value_as_path("compile")?
-                .into_internal("Cannot canonicalize an empty value")
-                .into_step()?;
-            self.expect_end_of_self_close_tag("compile")?;
+            self.output_mut().into_step()?.clear_buffer();
 
-            // make sure we write all buffered content before spawning the sealed
-            // parser
-            self.output_mut().into_step()?.flush_buffer_to_content();
-            self.parse_sealed(path).into_step()?;
+            let condition = IfParser::parse_result("assert", self)?;
 
-            Ok(())
-        }
-    }
+            self.output_mut().into_step()?.clear_buffer();
+            self.bypass_whitespace()?;
+            self.expect_end_of_self_close_tag("assert")?;
 
-    fn parse_compil(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'e' => {
-                self.push_step()?;
-                self.parse_compile()
-            },
-            _ => self.unexpected_tag(),
+            if !condition.as_evaluation() {
+                return Err(Err(InternalError::new(
+                    "ASSERTION FAILED"
+                )));
+            }
+
+            Ok(())
         }
     }
 
-    fn parse_compi(&mut self) -> StepResult<()> {
+    fn parse_asser(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            't' => {
                 self.push_step()?;
-                self.parse_compil()
+                self.parse_assert()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_comp(&mut self) -> StepResult<()> {
+    fn parse_asse(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'r' => {
                 self.push_step()?;
-                self.parse_compi()
+                self.parse_asser()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_com(&mut self) -> StepResult<()> {
+    fn parse_ass(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'p' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_comp()
+                self.parse_asse()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_co(&mut self) -> StepResult<()> {
+    fn parse_as(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'm' => {
+            's' => {
                 self.push_step()?;
-                self.parse_com()
+                self.parse_ass()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_c(&mut self) -> StepResult<()> {
+    fn parse_a(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'a' => {
+            'd' => {
                 self.push_step()?;
-                self.parse_ca()
+                self.parse_ad()
             },
-            'o' => {
+            's' => {
                 self.push_step()?;
-                self.parse_co()
+                self.parse_as()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_div(&mut self) -> StepResult<()> {
+    fn parse_block_tag(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_tag();
             }
 
-            self.buffer_all_until_end_of_tag("div")?;
+            self.buffer_all_until_end_of_tag("block")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndDiv)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndBlock)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
             match end_position {
-                EndPosition::Div => {},
+                EndPosition::Block => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'div' tag, '{pos:?}'"
+                    "Invalid end position in 'block' tag, '{pos:?}'"
                 )))),
             };
 
@@ -760,388 +1318,2759 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let value = self.parse_value_as_number("div")?;
+            let name = self.parse_variable_name("block")?;
 
-            self.expect_end_of_tag("div")?;
+            self.bypass_whitespace()?;
+            self.expect_end_of_tag("block")?;
 
-            let content = self.parse_child(ParseUntil::EndDiv).into_step()?
-                .trim()
-                .parse::<i64>()
-                .into_internal("Failed to parse content as a number")
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndBlock)
                 .into_step()?;
 
-            self.output_mut().into_step()?
-                .write_str(&value.checked_div(content).unwrap_or(0).to_string());
-            self.output_mut().into_step()?.flush_buffer_to_content();
+            match end_position {
+                EndPosition::Block => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'block' tag, '{pos:?}'"
+                )))),
+            };
+
+            let path = self.input().into_step()?.path().to_owned();
+            let block = Block { content, path, };
+
+            // an extending template only defines its overrides, which are
+            // rendered by the outermost template in place of its own content
+            if self.extending {
+                self.blocks.entry(name).or_default().push(block);
+
+                return Ok(());
+            }
+
+            let mut blocks = self.blocks.get(&name).cloned().unwrap_or_default();
+            blocks.push(block);
+
+            self.parse_block(BlockState { name, blocks, index: 0, }).into_step()?;
+            self.propagate_loop_flow()?;
 
             Ok(())
         }
     }
 
-    fn parse_di(&mut self) -> StepResult<()> {
+    fn parse_bloc(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'v' => {
+            'k' => {
                 self.push_step()?;
-                self.parse_div()
+                self.parse_block_tag()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_d(&mut self) -> StepResult<()> {
+    fn parse_blo(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'c' => {
                 self.push_step()?;
-                self.parse_di()
+                self.parse_bloc()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_else(&mut self) -> StepResult<()> {
-        if self.bypass() {
-            match self.parse_until() {
-                ParseUntil::EndFordir|
-                ParseUntil::EndForeach|
-                ParseUntil::EndForfile|
-                ParseUntil::EndForsplit|
-                ParseUntil::EndIf => {},
-                _ => {
-                    return self.unexpected_tag();
-                },
-            }
+    fn parse_bl(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'o' => {
+                self.push_step()?;
+                self.parse_blo()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-            self.expect_end_of_tag_buffer("else")?;
-            self.set_end_position(EndPosition::Else);
+    fn parse_break(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            self.buffer_all_until_end_of_self_closing_tag("break")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            Err(Ok(FlowControl::Break))
+            Ok(())
         }
         else {
-            match self.parse_until() {
-                ParseUntil::EndFordir|
-                ParseUntil::EndForeach|
-                ParseUntil::EndForfile|
-                ParseUntil::EndForsplit|
-                ParseUntil::EndIf => {},
-                _ => {
-                    return self.unexpected_tag();
-                },
+            if self.current_loop.is_none() {
+                return Err(Err(InternalError::new("A 'break' tag must be within a loop")));
             }
 
             self.output_mut().into_step()?.clear_buffer();
-            self.expect_end_of_tag("else")?;
-            self.set_end_position(EndPosition::Else);
 
-            Err(Ok(FlowControl::Break))
+            let condition = if self.bypass_whitespace_enforce_one()?
+                && self.tag_current_or_unexpected_eof("break")? == 'i'
+            {
+                self.push_step()?;
+                self.tag_expect_char("break", |c| matches!(c, 'f'))?;
+
+                if !self.bypass_whitespace_enforce_one()? {
+                    return self.tag_unexpected_char("break");
+                }
+
+                self.output_mut().into_step()?.clear_buffer();
+
+                IfParser::parse_result("break", self)?.as_evaluation()
+            }
+            else {
+                true
+            };
+
+            self.expect_end_of_self_close_tag("break")?;
+
+            if condition {
+                self.set_end_position(EndPosition::Break);
+
+                Err(Ok(FlowControl::Break))
+            }
+            else {
+                Ok(())
+            }
         }
     }
 
-    fn parse_els(&mut self) -> StepResult<()> {
+    fn parse_brea(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'k' => {
+                self.push_step()?;
+                self.parse_break()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_bre(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_brea()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_br(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             'e' => {
                 self.push_step()?;
-                self.parse_else()
+                self.parse_bre()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_el(&mut self) -> StepResult<()> {
+    fn parse_b(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            's' => {
+            'l' => {
                 self.push_step()?;
-                self.parse_els()
+                self.parse_bl()
+            },
+            'r' => {
+                self.push_step()?;
+                self.parse_br()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_extend_tag(&mut self) -> StepResult<()> {
+    fn parse_call(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_tag();
             }
 
-            self.buffer_all_until_end_of_self_closing_tag("extend")?;
+            self.buffer_all_until_end_of_self_closing_tag("call")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
         else {
+            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
                 return self.unknown_tag();
             }
 
-            let path = self.parse_value_as_path("extend")?
-                .into_internal("The extended path was None and this message sucks")
+            let path = self.parse_value_as_path("call")?
+                .into_internal("Path was None and this message should be better")
                 .into_step()?;
-            self.set_extend(path);
+            self.expect_end_of_self_close_tag("call")?;
 
-            self.expect_end_of_self_close_tag("extend")?;
+            // make sure we write all buffered content before spawning the sealed
+            // parser
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.parse_unsealed(path).into_step()?;
 
             Ok(())
         }
     }
 
-    fn parse_exten(&mut self) -> StepResult<()> {
+    fn parse_cal(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
+            'l' => {
                 self.push_step()?;
-                self.parse_extend_tag()
+                self.parse_call()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_exte(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'n' => {
-                self.push_step()?;
-                self.parse_exten()
+    fn parse_case(&mut self) -> StepResult<()> {
+        match self.parse_until() {
+            ParseUntil::EndMatch => {},
+            _ => {
+                return self.unexpected_tag();
             },
-            _ => self.unexpected_tag(),
         }
-    }
 
-    fn parse_ext(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'e' => {
-                self.push_step()?;
-                self.parse_exte()
-            },
-            _ => self.unexpected_tag(),
+        // the values are left in the input for the parent 'match' to parse
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.set_end_position(EndPosition::Case);
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            self.set_end_position(EndPosition::Case);
+
+            Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_ex(&mut self) -> StepResult<()> {
+    fn parse_cas(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            't' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_ext()
+                self.parse_case()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_e(&mut self) -> StepResult<()> {
+    fn parse_ca(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             'l' => {
                 self.push_step()?;
-                self.parse_el()
+                self.parse_cal()
             },
-            'x' => {
+            's' => {
                 self.push_step()?;
-                self.parse_ex()
+                self.parse_cas()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_fordir(&mut self) -> StepResult<()> {
+    fn parse_compile(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_tag();
             }
 
-            self.buffer_all_until_end_of_tag("fordir")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndFordir)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
-
-            match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndFordir)
-                        .into_step()?;
-                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
-                },
-                EndPosition::Fordir => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'fordir' tag, '{pos:?}'"
-                )))),
-            };
-
+            self.buffer_all_until_end_of_self_closing_tag("compile")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
         else {
-            if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
-            }
-
             self.output_mut().into_step()?.clear_buffer();
-
-            let variable = self.parse_variable_name("fordir")?;
-
             if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'fordir' tag")));
+                return self.unknown_tag();
             }
 
-            self.tag_expect_char("fordir", |c| matches!(c, 'i'))?;
-            self.tag_expect_char("fordir", |c| matches!(c, 'n'))?;
+            let path = self.parse_value_as_path("compile")?
+                .into_internal("Cannot canonicalize an empty value")
+                .into_step()?;
+            self.expect_end_of_self_close_tag("compile")?;
 
-            if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'fordir' tag")));
-            }
+            // make sure we write all buffered content before spawning the sealed
+            // parser
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.parse_sealed(path).into_step()?;
 
-            let path = self.parse_value_as_path("fordir")?
-                .into_internal("Cannot iterate over directories within a None path")
-                .into_step()?;
+            Ok(())
+        }
+    }
 
-            self.bypass_whitespace()?;
+    fn parse_compil(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_compile()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-            let mut loop_variable = None;
-            if self.tag_current_or_unexpected_eof("fordir")? == 'a' {
+    fn parse_compi(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
                 self.push_step()?;
+                self.parse_compil()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-                match self.tag_current_or_unexpected_eof("fordir")? {
-                    's' => {
-                        self.push_step()?;
+    fn parse_comp(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_compi()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-                        self.bypass_whitespace()?;
+    fn parse_com(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'p' => {
+                self.push_step()?;
+                self.parse_comp()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-                        loop_variable = Some(self.parse_variable_name("fordir")?);
+    fn parse_continue(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            self.buffer_all_until_end_of_self_closing_tag("continue")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
 
-                        self.bypass_whitespace()?;
-                    },
-                    _ => return self.tag_unexpected_char("fordir"),
-                }
+            Ok(())
+        }
+        else {
+            if self.current_loop.is_none() {
+                return Err(Err(InternalError::new("A 'continue' tag must be within a loop")));
             }
 
-            let reversed = if self.tag_current_or_unexpected_eof("fordir")? == 'r' {
+            self.output_mut().into_step()?.clear_buffer();
+
+            let condition = if self.bypass_whitespace_enforce_one()?
+                && self.tag_current_or_unexpected_eof("continue")? == 'i'
+            {
                 self.push_step()?;
+                self.tag_expect_char("continue", |c| matches!(c, 'f'))?;
 
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'v'))?;
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'r'))?;
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'s'))?;
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("fordir", |c| c.eq(&'d'))?;
+                if !self.bypass_whitespace_enforce_one()? {
+                    return self.tag_unexpected_char("continue");
+                }
 
                 self.output_mut().into_step()?.clear_buffer();
-                self.bypass_whitespace()?;
 
-                true
+                IfParser::parse_result("continue", self)?.as_evaluation()
             }
             else {
-                false
-            };
-
-            self.expect_end_of_tag("fordir")?;
-
-            let mut dirpaths = path.read_dir()
-                .into_internal("Failed to read directory")
-                .into_step()?
-                .map(|direntry_res| direntry_res.map(|de| de.path()))
-                .collect::<io::Result<Vec<PathBuf>>>()
-                .into_internal("Failed to read paths of directory entries")
-                .into_step()?
-                .into_iter()
-                .filter(|direntry| direntry.is_dir())
-                .collect::<Vec<PathBuf>>();
-
-            dirpaths.sort_unstable_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndFordir)
-                .into_step()?;
-            let else_content = match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndFordir)
-                        .into_step()?;
-                    Some(else_content)
-                },
-                EndPosition::Fordir => None,
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'fordir' tag, '{pos:?}'"
-                )))),
+                true
             };
 
-            if !dirpaths.is_empty() {
-                let last = dirpaths.len();
-
-                if reversed {
-                    dirpaths.reverse();
-                }
-
-                let path = self.input().into_step()?.path().to_owned();
-                for (index, dirpath) in dirpaths.into_iter().enumerate() {
-                    let dirstr = dirpath.to_str().unwrap_or("").to_owned();
-                    self.context_mut().into_step()?.add_variable(&variable, &path, dirstr);
-
-                    if let Some(loop_variable) = loop_variable.clone() {
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.index"), &path, index.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.size"), &path, last.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.isfirst"), &path, if index == 0 { "1" } else { "0" });
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.islast"), &path, if index + 1 == last { "1" } else { "0" });
-                    }
+            self.expect_end_of_self_close_tag("continue")?;
 
-                    self.parse_limited(
-                        content.as_slice(),
-                        ParseUntil::EndFordir
-                    ).into_step()?;
+            if condition {
+                self.set_end_position(EndPosition::Continue);
 
-                    self.context_mut().into_step()?.pop_variable(&variable);
-                }
+                Err(Ok(FlowControl::Break))
             }
-            else if let Some(content) = else_content {
-                self.parse_limited(content.as_slice(), ParseUntil::EndFordir)
-                    .into_step()?;
+            else {
+                Ok(())
             }
-
-            Ok(())
         }
     }
 
-    fn parse_fordi(&mut self) -> StepResult<()> {
+    fn parse_continu(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'r' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_fordir()
+                self.parse_continue()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_ford(&mut self) -> StepResult<()> {
+    fn parse_contin(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'u' => {
                 self.push_step()?;
-                self.parse_fordi()
+                self.parse_continu()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_foreach(&mut self) -> StepResult<()> {
-        if self.bypass() {
+    fn parse_conti(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'n' => {
+                self.push_step()?;
+                self.parse_contin()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_cont(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_conti()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_con(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_cont()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_co(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'm' => {
+                self.push_step()?;
+                self.parse_com()
+            },
+            'n' => {
+                self.push_step()?;
+                self.parse_con()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_cycle(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("cycle")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let mut values = Vec::new();
+
+            loop {
+                values.push(self.parse_value("cycle")?);
+
+                self.bypass_whitespace()?;
+                match self.tag_current_or_unexpected_eof("cycle")? {
+                    ',' => {
+                        self.input_mut().into_step()?.step().into_step()?;
+                        self.bypass_whitespace()?;
+                    },
+                    _ => break,
+                }
+            }
+
+            self.expect_end_of_self_close_tag("cycle")?;
+
+            let index = self.current_loop.as_ref()
+                .map(|state| state.index)
+                .into_internal("A 'cycle' tag must be within a loop")
+                .into_step()?;
+
+            let context = self.context().into_step()?;
+            let output = values[index % values.len()].as_ref()
+                .to_output(&[])
+                .into_step()?;
+            let output = context.escape().apply(output);
+
+            self.output_mut().into_step()?.write_str(&output);
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+    }
+
+    fn parse_cycl(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_cycle()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_cyc(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_cycl()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_cy(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'c' => {
+                self.push_step()?;
+                self.parse_cyc()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_c(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_ca()
+            },
+            'o' => {
+                self.push_step()?;
+                self.parse_co()
+            },
+            'y' => {
+                self.push_step()?;
+                self.parse_cy()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_default(&mut self) -> StepResult<()> {
+        match self.parse_until() {
+            ParseUntil::EndMatch => {},
+            _ => {
+                return self.unexpected_tag();
+            },
+        }
+
+        if self.bypass() {
+            self.expect_end_of_tag_buffer("default")?;
+            self.set_end_position(EndPosition::Default);
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            self.expect_end_of_tag("default")?;
+            self.set_end_position(EndPosition::Default);
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_defaul(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_default()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_defau(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_defaul()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_defa(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'u' => {
+                self.push_step()?;
+                self.parse_defau()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_def(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_defa()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_de(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'f' => {
+                self.push_step()?;
+                self.parse_def()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_div(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("div")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndDiv)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Div => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'div' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let value = self.parse_value_as_number("div")?;
+
+            self.expect_end_of_tag("div")?;
+
+            let content = self.parse_child_as_number(ParseUntil::EndDiv)?;
+
+            self.output_mut().into_step()?.write_str(&value.div(content).into_step()?.to_string());
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+    }
+
+    fn parse_di(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'v' => {
+                self.push_step()?;
+                self.parse_div()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_d(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_de()
+            },
+            'i' => {
+                self.push_step()?;
+                self.parse_di()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_else(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            match self.parse_until() {
+                ParseUntil::EndFordir|
+                ParseUntil::EndForeach|
+                ParseUntil::EndForfile|
+                ParseUntil::EndForkeys|
+                ParseUntil::EndForline|
+                ParseUntil::EndForrange|
+                ParseUntil::EndForsplit|
+                ParseUntil::EndIf => {},
+                _ => {
+                    return self.unexpected_tag();
+                },
+            }
+
+            self.expect_end_of_tag_buffer("else")?;
+            self.set_end_position(EndPosition::Else);
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            match self.parse_until() {
+                ParseUntil::EndFordir|
+                ParseUntil::EndForeach|
+                ParseUntil::EndForfile|
+                ParseUntil::EndForkeys|
+                ParseUntil::EndForline|
+                ParseUntil::EndForrange|
+                ParseUntil::EndForsplit|
+                ParseUntil::EndIf => {},
+                _ => {
+                    return self.unexpected_tag();
+                },
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+            self.expect_end_of_tag("else")?;
+            self.set_end_position(EndPosition::Else);
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_els(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_else()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_elif(&mut self) -> StepResult<()> {
+        match self.parse_until() {
+            ParseUntil::EndIf => {},
+            _ => {
+                return self.unexpected_tag();
+            },
+        }
+
+        // the condition is left in the input for the parent 'if' to parse
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.set_end_position(EndPosition::Elif);
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            self.set_end_position(EndPosition::Elif);
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_eli(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'f' => {
+                self.push_step()?;
+                self.parse_elif()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_el(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_eli()
+            },
+            's' => {
+                self.push_step()?;
+                self.parse_els()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_extend_tag(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("extend")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            let path = self.parse_value_as_path("extend")?
+                .into_internal("The extended path was None and this message sucks")
+                .into_step()?;
+            self.set_extend(path);
+
+            self.expect_end_of_self_close_tag("extend")?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_exten(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'd' => {
+                self.push_step()?;
+                self.parse_extend_tag()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_exte(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'n' => {
+                self.push_step()?;
+                self.parse_exten()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_ext(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_exte()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_ex(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_ext()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_e(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_el()
+            },
+            'x' => {
+                self.push_step()?;
+                self.parse_ex()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_fordir(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("fordir")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndFordir)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndFordir)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Fordir => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'fordir' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("fordir")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'fordir' tag")));
+            }
+
+            self.tag_expect_char("fordir", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("fordir", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'fordir' tag")));
+            }
+
+            let path = self.parse_value_as_path("fordir")?
+                .into_internal("Cannot iterate over directories within a None path")
+                .into_step()?;
+
+            let mut listing = Listing::new(Entry::Dir);
+            let loop_variable = self.parse_listing("fordir", &mut listing)?;
+
+            let dirpaths = listing.list(&path).into_step()?;
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndFordir)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndFordir)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Fordir => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'fordir' tag, '{pos:?}'"
+                )))),
+            };
+
+            let values: Vec<Value> = dirpaths.into_iter()
+                .map(|p| Value::from(p.to_str().unwrap_or("")))
+                .collect();
+
+            self.parse_loop(
+                ParseUntil::EndFordir, LoopVariables::new(variable, loop_variable),
+                values, Vec::new(), &content, else_content,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_fordi(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'r' => {
+                self.push_step()?;
+                self.parse_fordir()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_ford(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_fordi()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_foreach(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("foreach")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForeach)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForeach)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Foreach => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'foreach' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("foreach")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'foreach' tag")));
+            }
+
+            self.tag_expect_char("foreach", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("foreach", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'foreach' tag")));
+            }
+
+            let alias = self.parse_variable_name("foreach")?;
+
+            self.bypass_whitespace()?;
+
+            let mut loop_variable = None;
+            if self.tag_current_or_unexpected_eof("foreach")? == 'a' {
+                self.push_step()?;
+
+                match self.tag_current_or_unexpected_eof("foreach")? {
+                    's' => {
+                        self.push_step()?;
+
+                        self.bypass_whitespace()?;
+
+                        loop_variable = Some(self.parse_variable_name("foreach")?);
+
+                        self.bypass_whitespace()?;
+                    },
+                    _ => return self.tag_unexpected_char("foreach"),
+                }
+            }
+
+            let reversed = if self.tag_current_or_unexpected_eof("foreach")? == 'r' {
+                self.push_step()?;
+
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'v'))?;
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'r'))?;
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'s'))?;
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("foreach", |c| c.eq(&'d'))?;
+
+                self.output_mut().into_step()?.clear_buffer();
+                self.bypass_whitespace()?;
+
+                true
+            }
+            else {
+                false
+            };
+
+            self.expect_end_of_tag("foreach")?;
+
+            let mut values = self.context().into_step()?
+                .values(&alias)
+                .unwrap_or(vec![])
+                .into_iter()
+                .flat_map(|v| v.to_owned().into_vec())
+                .collect::<Vec<Value>>();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForeach)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForeach)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Foreach => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'foreach' tag, '{pos:?}'"
+                )))),
+            };
+
+            if reversed {
+                values.reverse();
+            }
+
+            self.parse_loop(
+                ParseUntil::EndForeach, LoopVariables::new(variable, loop_variable),
+                values, Vec::new(), &content, else_content,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_foreac(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'h' => {
+                self.push_step()?;
+                self.parse_foreach()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forea(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'c' => {
+                self.push_step()?;
+                self.parse_foreac()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_fore(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_forea()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forfile(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("forfile")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForfile)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForfile)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Forfile => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forfile' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("forfile")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forfile' tag")));
+            }
+
+            self.tag_expect_char("forfile", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("forfile", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forfile' tag")));
+            }
+
+            let path = self.parse_value_as_path("forfile")?
+                .into_internal("Cannot iterate over files within a None path")
+                .into_step()?;
+
+            let mut listing = Listing::new(Entry::File);
+            let loop_variable = self.parse_listing("forfile", &mut listing)?;
+
+            let filepaths = listing.list(&path).into_step()?;
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForfile)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForfile)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Forfile => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forfile' tag, '{pos:?}'"
+                )))),
+            };
+
+            let values: Vec<Value> = filepaths.into_iter()
+                .map(|p| Value::from(p.to_str().unwrap_or("")))
+                .collect();
+
+            // each file's front matter is stored beside it, i.e. `item.meta`
+            let variables = LoopVariables {
+                meta: Some(format!("{variable}.meta")),
+                ..LoopVariables::new(variable, loop_variable)
+            };
+
+            self.parse_loop(
+                ParseUntil::EndForfile, variables, values, Vec::new(), &content, else_content,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_forfil(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_forfile()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forfi(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_forfil()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forf(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_forfi()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forkeys(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("forkeys")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForkeys)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForkeys)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Forkeys => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forkeys' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let key = self.parse_variable_name("forkeys")?;
+
+            self.bypass_whitespace()?;
+            self.tag_expect_char("forkeys", |c| matches!(c, ','))?;
+            self.bypass_whitespace()?;
+
+            let variable = self.parse_variable_name("forkeys")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forkeys' tag")));
+            }
+
+            self.tag_expect_char("forkeys", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("forkeys", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forkeys' tag")));
+            }
+
+            let alias = self.parse_variable_name("forkeys")?;
+
+            self.bypass_whitespace()?;
+
+            let mut loop_variable = None;
+            if self.tag_current_or_unexpected_eof("forkeys")? == 'a' {
+                self.push_step()?;
+
+                match self.tag_current_or_unexpected_eof("forkeys")? {
+                    's' => {
+                        self.push_step()?;
+
+                        self.bypass_whitespace()?;
+
+                        loop_variable = Some(self.parse_variable_name("forkeys")?);
+
+                        self.bypass_whitespace()?;
+                    },
+                    _ => return self.tag_unexpected_char("forkeys"),
+                }
+            }
+
+            let reversed = if self.tag_current_or_unexpected_eof("forkeys")? == 'r' {
+                self.push_step()?;
+
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'v'))?;
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'r'))?;
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'s'))?;
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forkeys", |c| c.eq(&'d'))?;
+
+                self.output_mut().into_step()?.clear_buffer();
+                self.bypass_whitespace()?;
+
+                true
+            }
+            else {
+                false
+            };
+
+            self.expect_end_of_tag("forkeys")?;
+
+            let mut entries = self.context().into_step()?
+                .object(&alias)
+                .map(|object| object.entries())
+                .unwrap_or_default();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForkeys)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForkeys)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Forkeys => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forkeys' tag, '{pos:?}'"
+                )))),
+            };
+
+            if reversed {
+                entries.reverse();
+            }
+
+            let (keys, values): (Vec<Value>, Vec<Value>) = entries.into_iter().unzip();
+            let variables = LoopVariables {
+                key: Some(key),
+                ..LoopVariables::new(variable, loop_variable)
+            };
+
+            self.parse_loop(
+                ParseUntil::EndForkeys, variables, values, keys, &content, else_content,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_forkey(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            's' => {
+                self.push_step()?;
+                self.parse_forkeys()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forke(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'y' => {
+                self.push_step()?;
+                self.parse_forkey()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_fork(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_forke()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forline(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("forline")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForline)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForline)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Forline => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forline' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("forline")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forline' tag")));
+            }
+
+            self.tag_expect_char("forline", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("forline", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forline' tag")));
+            }
+
+            // a string is the path to a file, a variable holds the text itself
+            let (path, text) = if self.tag_current_or_unexpected_eof("forline")? == '"' {
+                (Some(self.parse_text_as_path("forline")?), String::new())
+            }
+            else {
+                (None, self.parse_value_as_string("forline")?.unwrap_or_default())
+            };
+
+            let mut loop_variable = None;
+            let mut reversed = false;
+            let mut skip_blank = false;
+            let mut comment = None;
+
+            loop {
+                self.bypass_whitespace()?;
+
+                if self.tag_current_or_unexpected_eof("forline")? == '%' {
+                    break;
+                }
+
+                let option = self.parse_variable_name("forline")?;
+
+                match option.as_str() {
+                    "as" => {
+                        loop_variable = Some(self.parse_variable_name("forline")?);
+                    },
+                    "comment" => {
+                        self.bypass_whitespace()?;
+
+                        let prefix = self.parse_value_as_string("forline")?
+                            .into_internal("The comment prefix of a 'forline' tag was None")
+                            .into_step()?;
+
+                        comment = Some(prefix);
+                    },
+                    "reversed" => reversed = true,
+                    "skipblank" => skip_blank = true,
+                    _ => return Err(Err(InternalError::new(format!(
+                        "Unknown option '{option}' in 'forline' tag"
+                    )))),
+                }
+            }
+
+            self.expect_end_of_tag("forline")?;
+
+            let source = LineSource { path, text, skip_blank, comment, };
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForline)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForline)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Forline => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forline' tag, '{pos:?}'"
+                )))),
+            };
+
+            let variables = LoopVariables::new(variable, loop_variable);
+
+            // the lines are only held in memory when they are reversed
+            if reversed {
+                let mut values = source.read()
+                    .into_step()?
+                    .map(|line| line.map(Value::from))
+                    .collect::<InternalResult<Vec<Value>>>()
+                    .into_step()?;
+                values.reverse();
+
+                self.parse_loop(ParseUntil::EndForline, variables, values, Vec::new(), &content, else_content)?;
+            }
+            else {
+                let values = LineValues::new(&source).into_step()?;
+
+                self.parse_loop(ParseUntil::EndForline, variables, values, Vec::new(), &content, else_content)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn parse_forlin(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_forline()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forli(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'n' => {
+                self.push_step()?;
+                self.parse_forlin()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forl(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_forli()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forrange(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("forrange")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForrange)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForrange)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Forrange => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forrange' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("forrange")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forrange' tag")));
+            }
+
+            self.tag_expect_char("forrange", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("forrange", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forrange' tag")));
+            }
+
+            let start = self.parse_value_as_number("forrange")?;
+
+            self.bypass_whitespace()?;
+
+            self.tag_expect_char("forrange", |c| matches!(c, 't'))?;
+            self.tag_expect_char("forrange", |c| matches!(c, 'o'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forrange' tag")));
+            }
+
+            let end = self.parse_value_as_number("forrange")?;
+
+            self.bypass_whitespace()?;
+
+            let step = if self.tag_current_or_unexpected_eof("forrange")? == 's' {
+                self.push_step()?;
+
+                self.tag_expect_char("forrange", |c| matches!(c, 't'))?;
+                self.tag_expect_char("forrange", |c| matches!(c, 'e'))?;
+                self.tag_expect_char("forrange", |c| matches!(c, 'p'))?;
+
+                if !self.bypass_whitespace_enforce_one()? {
+                    return Err(Err(InternalError::new("Unexpected character in 'forrange' tag")));
+                }
+
+                let step = self.parse_value_as_number("forrange")?;
+
+                self.bypass_whitespace()?;
+
+                step
+            }
+            else {
+                Number::Integer(1)
+            };
+
+            let mut loop_variable = None;
+            if self.tag_current_or_unexpected_eof("forrange")? == 'a' {
+                self.push_step()?;
+
+                match self.tag_current_or_unexpected_eof("forrange")? {
+                    's' => {
+                        self.push_step()?;
+
+                        self.bypass_whitespace()?;
+
+                        loop_variable = Some(self.parse_variable_name("forrange")?);
+
+                        self.bypass_whitespace()?;
+                    },
+                    _ => return self.tag_unexpected_char("forrange"),
+                }
+            }
+
+            let reversed = if self.tag_current_or_unexpected_eof("forrange")? == 'r' {
+                self.push_step()?;
+
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'v'))?;
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'r'))?;
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'s'))?;
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forrange", |c| c.eq(&'d'))?;
+
+                self.output_mut().into_step()?.clear_buffer();
+                self.bypass_whitespace()?;
+
+                true
+            }
+            else {
+                false
+            };
+
+            self.expect_end_of_tag("forrange")?;
+
+            let (start, end, step) = match (start, end, step) {
+                (Number::Integer(start), Number::Integer(end), Number::Integer(step)) => (start, end, step),
+                _ => return Err(Err(InternalError::new("The bounds and step of a 'forrange' tag must be integers"))),
+            };
+
+            if step <= 0 {
+                return Err(Err(InternalError::new("The step of a 'forrange' tag must be a positive integer")));
+            }
+
+            let values = RangeValues::new(start, end, step, reversed).into_step()?;
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForrange)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForrange)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Forrange => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forrange' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.parse_loop(
+                ParseUntil::EndForrange, LoopVariables::new(variable, loop_variable),
+                values, Vec::new(), &content, else_content,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_forrang(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_forrange()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forran(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'g' => {
+                self.push_step()?;
+                self.parse_forrang()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forra(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'n' => {
+                self.push_step()?;
+                self.parse_forran()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forr(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_forra()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forsplit(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("forsplit")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForsplit)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForsplit)
+                        .into_step()?;
+                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                },
+                EndPosition::Forsplit => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forsplit' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("forsplit")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forsplit' tag")));
+            }
+
+            self.tag_expect_char("forsplit", |c| matches!(c, 'i'))?;
+            self.tag_expect_char("forsplit", |c| matches!(c, 'n'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'forsplit' tag")));
+            }
+
+            let str_value = self.parse_value_as_string("forsplit")?;
+
+            self.bypass_whitespace()?;
+
+            self.tag_expect_char("forsplit", |c| matches!(c, 'o'))?;
+            self.tag_expect_char("forsplit", |c| matches!(c, 'n'))?;
+
+            self.bypass_whitespace()?;
+
+            let delimiter = self.parse_value_as_string("forsplit")?;
+
+            self.bypass_whitespace()?;
+
+            let mut loop_variable = None;
+            if self.tag_current_or_unexpected_eof("forsplit")? == 'a' {
+                self.push_step()?;
+
+                match self.tag_current_or_unexpected_eof("forsplit")? {
+                    's' => {
+                        self.push_step()?;
+
+                        self.bypass_whitespace()?;
+
+                        loop_variable = Some(self.parse_variable_name("forsplit")?);
+
+                        self.bypass_whitespace()?;
+                    },
+                    _ => return self.tag_unexpected_char("forsplit"),
+                }
+            }
+
+            let reversed = if self.tag_current_or_unexpected_eof("forsplit")? == 'r' {
+                self.push_step()?;
+
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'v'))?;
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'r'))?;
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'s'))?;
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'e'))?;
+                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'d'))?;
+
+                self.output_mut().into_step()?.clear_buffer();
+                self.bypass_whitespace()?;
+
+                true
+            }
+            else {
+                false
+            };
+
+            self.expect_end_of_tag("forsplit")?;
+
+            let mut values = match str_value {
+                Some(str_value) => match delimiter {
+                    Some(delimiter) => if !delimiter.is_empty() {
+                        str_value.split(&delimiter)
+                            .map(|s| s.to_owned())
+                            .collect::<Vec<String>>()
+                    }
+                    else {
+                        str_value.chars()
+                            .map(|c| c.to_string())
+                            .collect::<Vec<String>>()
+                    },
+                    None => str_value.chars().map(|c| c.to_string()).collect::<Vec<String>>(),
+                },
+                None => Vec::new(),
+            };
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForsplit)
+                .into_step()?;
+            let else_content = match end_position {
+                EndPosition::Else => {
+                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForsplit)
+                        .into_step()?;
+                    Some(else_content)
+                },
+                EndPosition::Forsplit => None,
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'forsplit' tag, '{pos:?}'"
+                )))),
+            };
+
+            if reversed {
+                values.reverse();
+            }
+
+            self.parse_loop(
+                ParseUntil::EndForsplit, LoopVariables::new(variable, loop_variable),
+                values.into_iter().map(Value::from).collect::<Vec<Value>>(), Vec::new(), &content, else_content,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_forspli(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_forsplit()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forspl(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_forspli()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_forsp(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_forspl()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_fors(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'p' => {
+                self.push_step()?;
+                self.parse_forsp()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_for(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'd' => {
+                self.push_step()?;
+                self.parse_ford()
+            },
+            'e' => {
+                self.push_step()?;
+                self.parse_fore()
+            },
+            'f' => {
+                self.push_step()?;
+                self.parse_forf()
+            },
+            'k' => {
+                self.push_step()?;
+                self.parse_fork()
+            },
+            'l' => {
+                self.push_step()?;
+                self.parse_forl()
+            },
+            'r' => {
+                self.push_step()?;
+                self.parse_forr()
+            },
+            's' => {
+                self.push_step()?;
+                self.parse_fors()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_fo(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'r' => {
+                self.push_step()?;
+                self.parse_for()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_fn(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("fn")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndFn)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Fn => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'fn' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let name = self.parse_variable_name("fn")?;
+            let args = self.parse_function_args("fn")?;
+
+            self.expect_end_of_tag("fn")?;
+
+            let (content, end_pos,) = self.parse_bypassed(ParseUntil::EndFn).into_step()?;
+            match end_pos {
+                EndPosition::Fn => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'fn' tag, '{pos:?}'"
+                )))),
+            }
+
+            let content_str = String::from_utf8(content)
+                .into_internal(format!("Invalid utf-8 in function body of '{name}'"))
+                .into_step()?;
+
+            self.context_mut().into_step()?.add_function(name, args, content_str);
+
+            Ok(())
+        }
+    }
+
+    fn parse_f(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'n' => {
+                self.push_step()?;
+                self.parse_fn()
+            },
+            'o' => {
+                self.push_step()?;
+                self.parse_fo()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_if(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_sequence("if", &['%', '}'])?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            loop {
+                let (content, end_position) = self.parse_bypassed(ParseUntil::EndIf)
+                    .into_step()?;
+                self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+                match end_position {
+                    EndPosition::Elif => {
+                        self.buffer_all_until_sequence("elif", &['%', '}'])?;
+                    },
+                    EndPosition::Else => {
+                        let (else_content, ..) = self.parse_bypassed(ParseUntil::EndIf)
+                            .into_step()?;
+                        self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
+                        break;
+                    },
+                    EndPosition::If => break,
+                    pos => return Err(Err(InternalError::new(format!(
+                        "Invalid end position in 'if' tag, '{pos:?}'"
+                    )))),
+                };
+            }
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let condition = IfParser::parse_result("if", self)?;
+
+            self.output_mut().into_step()?.clear_buffer();
+            self.bypass_whitespace()?;
+            self.expect_end_of_tag("if")?;
+
+            let (content, mut end_position) = self.parse_bypassed(ParseUntil::EndIf)
+                .into_step()?;
+            let mut matched = if condition.as_evaluation() {
+                Some(content)
+            }
+            else {
+                None
+            };
+
+            loop {
+                match end_position {
+                    EndPosition::Elif => {
+                        // once a block has matched, the remaining conditions
+                        // are never evaluated
+                        let condition = if matched.is_none() {
+                            let condition = IfParser::parse_result("elif", self)?;
+
+                            self.output_mut().into_step()?.clear_buffer();
+                            self.bypass_whitespace()?;
+                            self.expect_end_of_tag("elif")?;
+
+                            condition.as_evaluation()
+                        }
+                        else {
+                            self.buffer_all_until_sequence("elif", &['%', '}'])?;
+                            self.output_mut().into_step()?.clear_buffer();
+
+                            false
+                        };
+
+                        let (elif_content, elif_end_position) = self.parse_bypassed(ParseUntil::EndIf)
+                            .into_step()?;
+                        if condition {
+                            matched = Some(elif_content);
+                        }
+
+                        end_position = elif_end_position;
+                    },
+                    EndPosition::Else => {
+                        let (else_content, else_end_position) = self.parse_bypassed(ParseUntil::EndIf)
+                            .into_step()?;
+                        if let EndPosition::Elif = else_end_position {
+                            return Err(Err(InternalError::new(
+                                "An 'elif' tag cannot follow an 'else' tag",
+                            )));
+                        }
+
+                        if matched.is_none() {
+                            matched = Some(else_content);
+                        }
+
+                        break;
+                    },
+                    EndPosition::If => break,
+                    pos => return Err(Err(InternalError::new(format!("Invalid end position in 'if' tag, '{pos:?}'")))),
+                }
+            }
+
+            // parse the matched block
+            if let Some(content) = matched {
+                self.parse_limited(content.as_slice(), ParseUntil::EndIf).into_step()?;
+                self.propagate_loop_flow()?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn parse_include(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("include")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let path = self.parse_value_as_path("include")?
+                .into_internal("The included path was None and this message needs improvement")
+                .into_step()?;
+            let file = OpenOptions::new()
+                .read(true)
+                .write(false)
+                .create(false)
+                .open(&path)
+                .into_internal(format!("Failed to open file {path:?}"))
+                .into_step()?;
+
+            self.output_mut().into_step()?.flush_buffer_and_file(file)
+                .into_step()?;
+
+            self.expect_end_of_self_close_tag("include")?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_includ(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_include()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_inclu(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'd' => {
+                self.push_step()?;
+                self.parse_includ()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_incl(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'u' => {
+                self.push_step()?;
+                self.parse_inclu()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_inc(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_incl()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_invoke(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("invoke")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndInvoke)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Invoke => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'invoke' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let name = self.parse_variable_name("invoke")?;
+            let args = self.parse_function_call_args("invoke")?;
+
+            self.bypass_whitespace()?;
+            self.expect_end_of_tag("invoke")?;
+
+            // the body is compiled in the caller's context before the call, as
+            // it would be written, and is then trusted as `CONTENT`
+            let content = self.render_child(ParseUntil::EndInvoke)?;
+
+            self.parse_function(&name, args, Some(content)).into_step()?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_invok(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_invoke()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_invo(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'k' => {
+                self.push_step()?;
+                self.parse_invok()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_inv(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'o' => {
+                self.push_step()?;
+                self.parse_invo()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_in(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'c' => {
+                self.push_step()?;
+                self.parse_inc()
+            },
+            'v' => {
+                self.push_step()?;
+                self.parse_inv()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_i(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'f' => {
+                self.push_step()?;
+                self.parse_if()
+            },
+            'n' => {
+                self.push_step()?;
+                self.parse_in()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_load(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("load")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("load")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'load' tag")));
+            }
+
+            self.tag_expect_char("load", |c| matches!(c, 'f'))?;
+            self.tag_expect_char("load", |c| matches!(c, 'r'))?;
+            self.tag_expect_char("load", |c| matches!(c, 'o'))?;
+            self.tag_expect_char("load", |c| matches!(c, 'm'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'load' tag")));
+            }
+
+            let path = self.parse_value_as_path("load")?
+                .into_internal("The loaded path was None")
+                .into_step()?;
+
+            self.bypass_whitespace()?;
+
+            let mut format = None;
+            if self.tag_current_or_unexpected_eof("load")? == 'a' {
+                self.push_step()?;
+                self.tag_expect_char("load", |c| matches!(c, 's'))?;
+
+                if !self.bypass_whitespace_enforce_one()? {
+                    return Err(Err(InternalError::new("Unexpected character in 'load' tag")));
+                }
+
+                let name = self.parse_variable_name("load")?;
+                format = Some(name.parse::<Format>().into_step()?);
+            }
+
+            self.expect_end_of_self_close_tag("load")?;
+
+            data::load(self.context_mut().into_step()?, &variable, path, format).into_step()?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_loa(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'd' => {
+                self.push_step()?;
+                self.parse_load()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_lo(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_loa()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_l(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'o' => {
+                self.push_step()?;
+                self.parse_lo()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_match(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_sequence("match", &['%', '}'])?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            loop {
+                let (content, end_position) = self.parse_bypassed(ParseUntil::EndMatch)
+                    .into_step()?;
+                self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+                match end_position {
+                    EndPosition::Case => {
+                        self.buffer_all_until_sequence("case", &['%', '}'])?;
+                    },
+                    EndPosition::Default => {},
+                    EndPosition::Match => break,
+                    pos => return Err(Err(InternalError::new(format!(
+                        "Invalid end position in 'match' tag, '{pos:?}'"
+                    )))),
+                }
+            }
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let value = self.parse_value("match")?;
+
+            self.bypass_whitespace()?;
+            self.expect_end_of_tag("match")?;
+
+            // only whitespace, escaped newlines and comments may come before
+            // the first case, the content ends with the opening of the tag
+            // which ended it
+            let (content, mut end_position) = self.parse_bypassed(ParseUntil::EndMatch)
+                .into_step()?;
+            let leading = content.windows(2)
+                .rposition(|w| w == b"{%")
+                .map(|i| &content[..i])
+                .unwrap_or(content.as_slice());
+            if !is_blank(leading) {
+                return Err(Err(InternalError::new(
+                    "Content in a 'match' tag must be within a 'case' or 'default' tag",
+                )));
+            }
+
+            let mut matched = None;
+            let mut default = None;
+
+            loop {
+                match end_position {
+                    EndPosition::Case => {
+                        if default.is_some() {
+                            return Err(Err(InternalError::new(
+                                "A 'case' tag cannot follow a 'default' tag",
+                            )));
+                        }
+
+                        let mut is_match = false;
+
+                        loop {
+                            self.bypass_whitespace()?;
+
+                            let case_value = self.parse_value("case")?;
+                            if Condition::eq(value.to_owned(), case_value).as_evaluation() {
+                                is_match = true;
+                            }
+
+                            self.bypass_whitespace()?;
+                            match self.tag_current_or_unexpected_eof("case")? {
+                                ',' => {
+                                    self.input_mut().into_step()?.step().into_step()?;
+                                },
+                                _ => break,
+                            }
+                        }
+
+                        self.output_mut().into_step()?.clear_buffer();
+                        self.expect_end_of_tag("case")?;
+
+                        let (case_content, case_end_position) = self.parse_bypassed(ParseUntil::EndMatch)
+                            .into_step()?;
+                        if is_match && matched.is_none() {
+                            matched = Some(case_content);
+                        }
+
+                        end_position = case_end_position;
+                    },
+                    EndPosition::Default => {
+                        if default.is_some() {
+                            return Err(Err(InternalError::new(
+                                "A 'match' tag can only contain one 'default' tag",
+                            )));
+                        }
+
+                        let (default_content, default_end_position) = self.parse_bypassed(ParseUntil::EndMatch)
+                            .into_step()?;
+                        default = Some(default_content);
+                        end_position = default_end_position;
+                    },
+                    EndPosition::Match => break,
+                    pos => return Err(Err(InternalError::new(format!("Invalid end position in 'match' tag, '{pos:?}'")))),
+                }
+            }
+
+            // parse the matched case or the default
+            if let Some(content) = matched.or(default) {
+                self.parse_limited(content.as_slice(), ParseUntil::EndMatch).into_step()?;
+                self.propagate_loop_flow()?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn parse_matc(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'h' => {
+                self.push_step()?;
+                self.parse_match()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_mat(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'c' => {
+                self.push_step()?;
+                self.parse_matc()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_ma(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_mat()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_mod(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("mod")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndMod)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Mod => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'mod' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let value = self.parse_value_as_number("mod")?;
+
+            self.expect_end_of_tag("mod")?;
+
+            let content = self.parse_child_as_number(ParseUntil::EndMod)?;
+
+            self.output_mut().into_step()?.write_str(&value.rem(content).into_step()?.to_string());
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+    }
+
+    fn parse_mo(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'd' => {
+                self.push_step()?;
+                self.parse_mod()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_mul(&mut self) -> StepResult<()> {
+        if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unexpected_tag();
             }
 
-            self.buffer_all_until_end_of_tag("foreach")?;
+            self.buffer_all_until_end_of_tag("mul")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForeach)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndMul)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
             match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForeach)
-                        .into_step()?;
-                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
-                },
-                EndPosition::Foreach => {},
+                EndPosition::Mul => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'foreach' tag, '{pos:?}'"
+                    "Invalid end position in 'mul' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let value = self.parse_value_as_number("mul")?;
+
+            self.expect_end_of_tag("mul")?;
+
+            let content = self.parse_child_as_number(ParseUntil::EndMul)?;
+
+            self.output_mut().into_step()?.write_str(&value.mul(content).into_step()?.to_string());
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+    }
+
+    fn parse_mu(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_mul()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_m(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_ma()
+            },
+            'o' => {
+                self.push_step()?;
+                self.parse_mo()
+            },
+            'u' => {
+                self.push_step()?;
+                self.parse_mu()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_nth(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
+            }
+
+            self.buffer_all_until_end_of_tag("nth")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndNth)
+                .into_step()?;
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Nth => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'nth' tag, '{pos:?}'"
                 )))),
             };
 
@@ -1149,6 +4078,167 @@ where
 
             Ok(())
         }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            self.bypass_whitespace()?;
+
+            let alias = self.parse_variable_name("nth")?;
+
+            self.expect_end_of_tag("nth")?;
+
+            let output = self.parse_child(ParseUntil::EndNth)?;
+            let values = self.context().into_step()?.values(&alias);
+
+            let trimmed = output.trim();
+
+            let was_neg_zero;
+            let idx = if trimmed == "-0" {
+                was_neg_zero = true;
+                0
+            }
+            else {
+                was_neg_zero = false;
+                output.trim()
+                    .parse::<i64>()
+                    .map_err(|_| InternalError::new(format!(
+                        "Content was not an integer:\n{output}"
+                    )))
+                    .into_step()?
+            };
+
+            let idx_to_take = if was_neg_zero || idx < 0_i64 {
+                values.as_ref().map_or(0_usize, |vs| vs.len()
+                    .saturating_sub(1)
+                    .saturating_sub((0 - idx) as usize)
+                )
+            }
+            else {
+                idx as usize
+            };
+
+            let value = values.and_then(|vs| vs.into_iter().nth(idx_to_take))
+                .to_output(&[])
+                .into_step()?;
+            let value = self.context().into_step()?.escape().apply(value);
+
+            self.output_mut().into_step()?.write_str(&value);
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+    }
+
+    fn parse_nt(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'h' => {
+                self.push_step()?;
+                self.parse_nth()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_n(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_nt()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_path(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("path")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let path = self.parse_value_as_string("path")?
+                .into_internal("Cannot canonicalize a path from an empty value")
+                .into_step()?;
+
+            self.bypass_whitespace()?;
+
+            let mut reldir = if self.tag_current_or_unexpected_eof("path")? == 'i' {
+                self.push_step()?;
+
+                self.tag_expect_char("path", |c| matches!(c, 'n'))?;
+                self.bypass_whitespace()?;
+
+                self.output_mut().into_step()?.clear_buffer();
+
+                self.parse_value_as_path("path")?
+                    .into_internal("Cannot create an absolute path from a None path")
+                    .into_step()?
+            }
+            else {
+                let mut input_path = self.input().into_step()?.path().to_owned();
+                input_path.pop();
+                input_path
+            };
+
+            reldir.push(path);
+
+            let dir = canonicalize(&reldir)
+                .into_internal(format!("Failed to canonicalize relative path {reldir:?}"))
+                .into_step()?;
+
+            self.bypass_whitespace()?;
+
+            self.output_mut().into_step()?.clear_buffer();
+            self.output_mut().into_step()?.write_str(dir.to_str().unwrap_or(""));
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            self.expect_end_of_self_close_tag("path")?;
+
+            Ok(())
+        }
+    }
+
+    fn parse_pat(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'h' => {
+                self.push_step()?;
+                self.parse_path()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_pa(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_pat()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_pop(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("pop")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
         else {
             if !self.bypass_whitespace_enforce_one()? {
                 return self.unknown_tag();
@@ -1156,172 +4246,105 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let variable = self.parse_variable_name("foreach")?;
-
-            if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'foreach' tag")));
-            }
-
-            self.tag_expect_char("foreach", |c| matches!(c, 'i'))?;
-            self.tag_expect_char("foreach", |c| matches!(c, 'n'))?;
-
-            if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'foreach' tag")));
-            }
-
-            let alias = self.parse_variable_name("foreach")?;
-
-            self.bypass_whitespace()?;
-
-            let mut loop_variable = None;
-            if self.tag_current_or_unexpected_eof("foreach")? == 'a' {
-                self.push_step()?;
-
-                match self.tag_current_or_unexpected_eof("foreach")? {
-                    's' => {
-                        self.push_step()?;
-
-                        self.bypass_whitespace()?;
-
-                        loop_variable = Some(self.parse_variable_name("foreach")?);
-
-                        self.bypass_whitespace()?;
-                    },
-                    _ => return self.tag_unexpected_char("foreach"),
-                }
-            }
+            let variable = self.parse_variable_name("pop")?;
 
-            let reversed = if self.tag_current_or_unexpected_eof("foreach")? == 'r' {
-                self.push_step()?;
+            self.expect_end_of_self_close_tag("pop")?;
 
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'v'))?;
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'r'))?;
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'s'))?;
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("foreach", |c| c.eq(&'d'))?;
+            self.context_mut().into_step()?.pop_variable(variable);
 
-                self.output_mut().into_step()?.clear_buffer();
-                self.bypass_whitespace()?;
+            Ok(())
+        }
+    }
 
-                true
+    fn parse_pow(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unexpected_tag();
             }
-            else {
-                false
-            };
 
-            self.expect_end_of_tag("foreach")?;
-
-            let mut values = self.context().into_step()?
-                .values(&alias)
-                .unwrap_or(vec![])
-                .into_iter()
-                .map(|v| v.to_owned())
-                .collect::<Vec<String>>();
+            self.buffer_all_until_end_of_tag("pow")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForeach)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndPow)
                 .into_step()?;
-            let else_content = match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForeach)
-                        .into_step()?;
-                    Some(else_content)
-                },
-                EndPosition::Foreach => None,
+            self.output_mut().into_step()?.write_bytes_to_buffer(content);
+
+            match end_position {
+                EndPosition::Pow => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'foreach' tag, '{pos:?}'"
+                    "Invalid end position in 'pow' tag, '{pos:?}'"
                 )))),
             };
 
-            if !values.is_empty() {
-                let last = values.len();
+            self.output_mut().into_step()?.flush_buffer_to_content();
 
-                if reversed {
-                    values.reverse();
-                }
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
 
-                let path = self.input().into_step()?.path().to_owned();
-                for (index, value) in values.into_iter().enumerate() {
-                    self.context_mut().into_step()?.add_variable(&variable, &path, value.clone());
-
-                    if let Some(loop_variable) = loop_variable.clone() {
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.index"), &path, index.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.size"), &path, last.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.isfirst"), &path, if index == 0 { "1" } else { "0" });
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.islast"), &path, if index + 1 == last { "1" } else { "0" });
-                    }
+            self.output_mut().into_step()?.clear_buffer();
 
-                    self.parse_limited(content.as_slice(), ParseUntil::EndForeach).into_step()?;
+            let value = self.parse_value_as_number("pow")?;
 
-                    self.context_mut().into_step()?.pop_variable(&variable);
-                }
-            }
-            else if let Some(content) = else_content {
-                self.parse_limited(content.as_slice(), ParseUntil::EndForeach)
-                    .into_step()?;
-            }
+            self.expect_end_of_tag("pow")?;
+
+            let content = self.parse_child_as_number(ParseUntil::EndPow)?;
+
+            self.output_mut().into_step()?.write_str(&value.pow(content).into_step()?.to_string());
+            self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
     }
 
-    fn parse_foreac(&mut self) -> StepResult<()> {
+    fn parse_po(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'h' => {
+            'p' => {
                 self.push_step()?;
-                self.parse_foreach()
+                self.parse_pop()
             },
-            _ => self.unexpected_tag(),
-        }
-    }
-
-    fn parse_forea(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'c' => {
+            'w' => {
                 self.push_step()?;
-                self.parse_foreac()
+                self.parse_pow()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_fore(&mut self) -> StepResult<()> {
+    fn parse_p(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             'a' => {
                 self.push_step()?;
-                self.parse_forea()
+                self.parse_pa()
+            },
+            'o' => {
+                self.push_step()?;
+                self.parse_po()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_forfile(&mut self) -> StepResult<()> {
+    fn parse_query(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unexpected_tag();
             }
 
-            self.buffer_all_until_end_of_tag("forfile")?;
+            self.buffer_all_until_end_of_tag("query")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForfile)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndQuery)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
             match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForfile)
-                        .into_step()?;
-                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
-                },
-                EndPosition::Forfile => {},
+                EndPosition::Query => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'forfile' tag, '{pos:?}'"
+                    "Invalid end position in 'query' tag, '{pos:?}'"
                 )))),
             };
 
@@ -1336,187 +4359,148 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let variable = self.parse_variable_name("forfile")?;
+            let variable = self.parse_variable_name("query")?;
 
             if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'forfile' tag")));
+                return Err(Err(InternalError::new("Unexpected character in 'query' tag")));
             }
 
-            self.tag_expect_char("forfile", |c| matches!(c, 'i'))?;
-            self.tag_expect_char("forfile", |c| matches!(c, 'n'))?;
+            self.tag_expect_char("query", |c| matches!(c, 'f'))?;
+            self.tag_expect_char("query", |c| matches!(c, 'r'))?;
+            self.tag_expect_char("query", |c| matches!(c, 'o'))?;
+            self.tag_expect_char("query", |c| matches!(c, 'm'))?;
 
             if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'forfile' tag")));
+                return Err(Err(InternalError::new("Unexpected character in 'query' tag")));
             }
 
-            let path = self.parse_value_as_path("forfile")?
-                .into_internal("Cannot iterate over files within a None path")
+            let database = self.parse_value_as_path("query")?
+                .into_internal("Cannot open a database from an empty value")
                 .into_step()?;
 
-            self.bypass_whitespace()?;
-
-            let mut loop_variable = None;
-            if self.tag_current_or_unexpected_eof("forfile")? == 'a' {
-                self.push_step()?;
-
-                match self.tag_current_or_unexpected_eof("forfile")? {
-                    's' => {
-                        self.push_step()?;
-
-                        self.bypass_whitespace()?;
-
-                        loop_variable = Some(self.parse_variable_name("forfile")?);
-
-                        self.bypass_whitespace()?;
-                    },
-                    _ => return self.tag_unexpected_char("forfile"),
-                }
-            }
-
-            let reversed = if self.tag_current_or_unexpected_eof("forfile")? == 'r' {
-                self.push_step()?;
-
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'v'))?;
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'r'))?;
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'s'))?;
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("forfile", |c| c.eq(&'d'))?;
+            self.expect_end_of_tag("query")?;
 
-                self.output_mut().into_step()?.clear_buffer();
-                self.bypass_whitespace()?;
-
-                true
-            }
-            else {
-                false
-            };
+            let sql = self.parse_child(ParseUntil::EndQuery)?;
 
-            self.expect_end_of_tag("forfile")?;
-
-            let mut filepaths = path.read_dir()
-                .into_internal("Failed to read directory")
-                .into_step()?
-                .map(|direntry_res| direntry_res.map(|de| de.path()))
-                .collect::<io::Result<Vec<PathBuf>>>()
-                .into_internal("Failed to read paths of directory entries")
-                .into_step()?
-                .into_iter()
-                .filter(|direntry| direntry.is_file())
-                .collect::<Vec<PathBuf>>();
-
-            filepaths.sort_unstable_by(|a, b| a.file_name().cmp(&b.file_name()));
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForfile)
+            let connection = Connection::open_with_flags(&database, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .into_internal(format!("Failed to open database {database:?}"))
                 .into_step()?;
-            let else_content = match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForfile)
-                        .into_step()?;
-                    Some(else_content)
-                },
-                EndPosition::Forfile => None,
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'forfile' tag, '{pos:?}'"
-                )))),
-            };
-
-            if !filepaths.is_empty() {
-                let last = filepaths.len();
-
-                if reversed {
-                    filepaths.reverse();
-                }
-
-                let path = self.input().into_step()?.path().to_owned();
-                for (index, filepath) in filepaths.into_iter().enumerate() {
-                    let filestr = filepath.to_str().unwrap_or("").to_owned();
-                    self.context_mut().into_step()?.add_variable(&variable, &path, filestr);
-
-                    if let Some(loop_variable) = loop_variable.clone() {
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.index"), &path, index.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.size"), &path, last.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.isfirst"), &path, if index == 0 { "1" } else { "0" });
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.islast"), &path, if index + 1 == last { "1" } else { "0" });
-                    }
-
-                    self.parse_limited(
-                        content.as_slice(),
-                        ParseUntil::EndForfile
-                    ).into_step()?;
+            let mut stmt = connection.prepare(&sql)
+                .into_internal(format!("Failed to prepare query in 'query' tag: {sql}"))
+                .into_step()?;
+            let rows = self.context().into_step()?.query(&mut stmt).into_step()?;
 
-                    self.context_mut().into_step()?.pop_variable(&variable);
-                }
-            }
-            else if let Some(content) = else_content {
-                self.parse_limited(content.as_slice(), ParseUntil::EndForfile)
-                    .into_step()?;
-            }
+            let path = self.input().into_step()?.path().to_owned();
+            self.context_mut().into_step()?.add_variable(variable, path, rows);
 
             Ok(())
         }
     }
 
-    fn parse_forfil(&mut self) -> StepResult<()> {
+    fn parse_quer(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'e' => {
+            'y' => {
                 self.push_step()?;
-                self.parse_forfile()
+                self.parse_query()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_forfi(&mut self) -> StepResult<()> {
+    fn parse_que(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            'r' => {
                 self.push_step()?;
-                self.parse_forfil()
+                self.parse_quer()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_forf(&mut self) -> StepResult<()> {
+    fn parse_qu(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_forfi()
+                self.parse_que()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_forsplit(&mut self) -> StepResult<()> {
-        if self.bypass() {
-            if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
-            }
+    fn parse_q(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'u' => {
+                self.push_step()?;
+                self.parse_qu()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-            self.buffer_all_until_end_of_tag("forsplit")?;
+    fn parse_raw(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            self.buffer_all_until_end_of_tag("raw")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForsplit)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndRaw)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
-            match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForsplit)
-                        .into_step()?;
-                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
-                },
-                EndPosition::Forsplit => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'forsplit' tag, '{pos:?}'"
-                )))),
-            };
+            match end_position {
+                EndPosition::Raw => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'raw' tag, '{pos:?}'"
+                )))),
+            };
+
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+
+            self.expect_end_of_tag("raw")?;
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndRaw)
+                .into_step()?;
+
+            match end_position {
+                EndPosition::Raw => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'raw' tag, '{pos:?}'"
+                )))),
+            };
+
+            let escape = self.context().into_step()?.escape();
+            self.context_mut().into_step()?.set_escape(Escape::None);
+
+            let result = self.parse_limited(content.as_slice(), ParseUntil::EndRaw);
+
+            self.context_mut().into_step()?.set_escape(escape);
+
+            result.into_step()?;
+            self.propagate_loop_flow()
+        }
+    }
+
+    fn parse_ra(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'w' => {
+                self.push_step()?;
+                self.parse_raw()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_read(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
 
+            self.buffer_all_until_end_of_self_closing_tag("read")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
@@ -1528,228 +4512,203 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let variable = self.parse_variable_name("forsplit")?;
+            let variable = self.parse_variable_name("read")?;
 
             if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'forsplit' tag")));
+                return Err(Err(InternalError::new("Unexpected character in 'read' tag")));
             }
 
-            self.tag_expect_char("forsplit", |c| matches!(c, 'i'))?;
-            self.tag_expect_char("forsplit", |c| matches!(c, 'n'))?;
+            self.tag_expect_char("read", |c| matches!(c, 'f'))?;
+            self.tag_expect_char("read", |c| matches!(c, 'r'))?;
+            self.tag_expect_char("read", |c| matches!(c, 'o'))?;
+            self.tag_expect_char("read", |c| matches!(c, 'm'))?;
 
             if !self.bypass_whitespace_enforce_one()? {
-                return Err(Err(InternalError::new("Unexpected character in 'forsplit' tag")));
+                return Err(Err(InternalError::new("Unexpected character in 'read' tag")));
             }
 
-            let str_value = self.parse_value("forsplit")?;
-
-            self.bypass_whitespace()?;
-
-            self.tag_expect_char("forsplit", |c| matches!(c, 'o'))?;
-            self.tag_expect_char("forsplit", |c| matches!(c, 'n'))?;
-
-            self.bypass_whitespace()?;
-
-            let delimiter = self.parse_value("forsplit")?;
-
-            self.bypass_whitespace()?;
-
-            let mut loop_variable = None;
-            if self.tag_current_or_unexpected_eof("forsplit")? == 'a' {
-                self.push_step()?;
+            let path = self.parse_value_as_path("read")?
+                .into_internal("Cannot read a None path")
+                .into_step()?;
 
-                match self.tag_current_or_unexpected_eof("forsplit")? {
-                    's' => {
-                        self.push_step()?;
+            let mut trim = false;
+            let mut default = None;
+            loop {
+                self.bypass_whitespace()?;
 
-                        self.bypass_whitespace()?;
+                if self.tag_current_or_unexpected_eof("read")? == '/' {
+                    break;
+                }
 
-                        loop_variable = Some(self.parse_variable_name("forsplit")?);
+                let option = self.parse_variable_name("read")?;
 
+                match option.as_str() {
+                    "default" => {
                         self.bypass_whitespace()?;
+                        default = Some(self.parse_value_as_string("read")?.unwrap_or_default());
                     },
-                    _ => return self.tag_unexpected_char("forsplit"),
+                    "trim" => trim = true,
+                    _ => return Err(Err(InternalError::new(format!(
+                        "Unknown option '{option}' in 'read' tag"
+                    )))),
                 }
             }
 
-            let reversed = if self.tag_current_or_unexpected_eof("forsplit")? == 'r' {
-                self.push_step()?;
-
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'v'))?;
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'r'))?;
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'s'))?;
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'e'))?;
-                self.tag_expect_buffer_char("forsplit", |c| c.eq(&'d'))?;
+            self.expect_end_of_self_close_tag("read")?;
 
-                self.output_mut().into_step()?.clear_buffer();
-                self.bypass_whitespace()?;
+            // the default only stands in for a file which does not exist
+            let text = match (read_to_string(&path), default) {
+                (Ok(text), _) => text,
+                (Err(e), Some(default)) if e.kind() == io::ErrorKind::NotFound => default,
+                (Err(e), _) => return Err(Err(InternalError::new(format!(
+                    "Failed to read file {path:?}: {e}"
+                )))),
+            };
 
-                true
+            let text = if trim {
+                text.trim().to_owned()
             }
             else {
-                false
+                text
             };
 
-            self.expect_end_of_tag("forsplit")?;
+            let input_path = self.input().into_step()?.path().to_owned();
+            self.context_mut().into_step()?.add_variable(variable, input_path, text);
 
-            let mut values = match str_value {
-                Some(str_value) => match delimiter {
-                    Some(delimiter) => if !delimiter.is_empty() {
-                        str_value.split(&delimiter)
-                            .map(|s| s.to_owned())
-                            .collect::<Vec<String>>()
-                    }
-                    else {
-                        str_value.chars()
-                            .map(|c| c.to_string())
-                            .collect::<Vec<String>>()
-                    },
-                    None => str_value.chars().map(|c| c.to_string()).collect::<Vec<String>>(),
-                },
-                None => Vec::new(),
-            };
+            Ok(())
+        }
+    }
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndForsplit)
-                .into_step()?;
-            let else_content = match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndForsplit)
-                        .into_step()?;
-                    Some(else_content)
-                },
-                EndPosition::Forsplit => None,
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'forsplit' tag, '{pos:?}'"
-                )))),
-            };
+    fn parse_rea(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'd' => {
+                self.push_step()?;
+                self.parse_read()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
 
-            if !values.is_empty() {
-                let last = values.len();
+    fn parse_return(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            self.buffer_all_until_end_of_self_closing_tag("return")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
 
-                if reversed {
-                    values.reverse();
-                }
+            Ok(())
+        }
+        else {
+            if !self.in_function {
+                return Err(Err(InternalError::new("A 'return' tag must be within a function")));
+            }
 
-                let path = self.input().into_step()?.path().to_owned();
-                for (index, value) in values.into_iter().enumerate() {
-                    self.context_mut().into_step()?.add_variable(&variable, &path, value.clone());
-
-                    if let Some(loop_variable) = loop_variable.clone() {
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.index"), &path, index.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.size"), &path, last.to_string());
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.isfirst"), &path, if index == 0 { "1" } else { "0" });
-                        self.context_mut().into_step()?
-                            .add_variable(format!("{loop_variable}.islast"), &path, if index + 1 == last { "1" } else { "0" });
-                    }
+            self.output_mut().into_step()?.clear_buffer();
 
-                    self.parse_limited(content.as_slice(), ParseUntil::EndForsplit).into_step()?;
+            let value = if self.bypass_whitespace_enforce_one()?
+                && self.tag_current_or_unexpected_eof("return")? != '/'
+            {
+                let value = self.parse_value("return")?.unwrap_or_default();
+                self.bypass_whitespace()?;
 
-                    self.context_mut().into_step()?.pop_variable(&variable);
-                }
-            }
-            else if let Some(content) = else_content {
-                self.parse_limited(content.as_slice(), ParseUntil::EndForsplit)
-                    .into_step()?;
+                value
             }
+            else {
+                Value::Null
+            };
 
-            Ok(())
-        }
-    }
+            self.expect_end_of_self_close_tag("return")?;
 
-    fn parse_forspli(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            't' => {
-                self.push_step()?;
-                self.parse_forsplit()
-            },
-            _ => self.unexpected_tag(),
+            self.returned = Some(value);
+            self.set_end_position(EndPosition::Return);
+
+            Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_forspl(&mut self) -> StepResult<()> {
+    fn parse_retur(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'n' => {
                 self.push_step()?;
-                self.parse_forspli()
+                self.parse_return()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_forsp(&mut self) -> StepResult<()> {
+    fn parse_retu(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            'r' => {
                 self.push_step()?;
-                self.parse_forspl()
+                self.parse_retur()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_fors(&mut self) -> StepResult<()> {
+    fn parse_ret(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'p' => {
+            'u' => {
                 self.push_step()?;
-                self.parse_forsp()
+                self.parse_retu()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_for(&mut self) -> StepResult<()> {
+    fn parse_re(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
-                self.push_step()?;
-                self.parse_ford()
-            },
-            'e' => {
-                self.push_step()?;
-                self.parse_fore()
-            },
-            'f' => {
+            'a' => {
                 self.push_step()?;
-                self.parse_forf()
+                self.parse_rea()
             },
-            's' => {
+            't' => {
                 self.push_step()?;
-                self.parse_fors()
+                self.parse_ret()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_fo(&mut self) -> StepResult<()> {
+    fn parse_r(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'r' => {
+            'a' => {
                 self.push_step()?;
-                self.parse_for()
+                self.parse_ra()
+            },
+            'e' => {
+                self.push_step()?;
+                self.parse_re()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_fn(&mut self) -> StepResult<()> {
+    fn parse_set(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unexpected_tag();
             }
 
-            self.buffer_all_until_end_of_tag("fn")?;
+            self.buffer_all_while(|c| matches!(c, variable_chars!()))?;
+            self.buffer_whitespace()?;
+
+            if let Some('=') = self.current()? {
+                self.buffer_all_until_end_of_self_closing_tag("set")?;
+                self.output_mut().into_step()?.flush_buffer_to_content();
+
+                return Ok(());
+            }
+
+            self.buffer_all_until_end_of_tag("set")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndFn)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndSet)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
             match end_position {
-                EndPosition::Fn => {},
+                EndPosition::Set => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'fn' tag, '{pos:?}'"
+                    "Invalid end position in 'set' tag, '{pos:?}'"
                 )))),
             };
 
@@ -1764,65 +4723,144 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let name = self.parse_variable_name("fn")?;
-            let args = self.parse_function_args("fn")?;
+            let variable = self.parse_variable_name("set")?;
 
-            self.expect_end_of_tag("fn")?;
+            self.bypass_whitespace()?;
 
-            let (content, end_pos,) = self.parse_bypassed(ParseUntil::EndFn).into_step()?;
-            match end_pos {
-                EndPosition::Fn => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'fn' tag, '{pos:?}'"
-                )))),
+            let path = self.input().into_step()?.path().to_owned();
+
+            if let Some('=') = self.current()? {
+                self.push_step()?;
+                self.bypass_whitespace()?;
+
+                let value = match self.tag_current_or_unexpected_eof("set")? {
+                    '"'|'-'|number_chars!() => self.parse_value("set")?,
+                    _ => {
+                        let alias = self.parse_variable_name("set")?;
+                        self.bypass_whitespace()?;
+
+                        // assigns the value returned by a function call
+                        if let Some('(') = self.current()? {
+                            let args = self.parse_function_call_args("set")?;
+                            self.call_function(&alias, args).into_step()?
+                        }
+                        else {
+                            self.context().into_step()?.object(&alias)
+                        }
+                    },
+                }.unwrap_or(Value::Null);
+
+                self.bypass_whitespace()?;
+                self.expect_end_of_self_close_tag("set")?;
+
+                self.context_mut().into_step()?.add_variable(variable, path, value);
+
+                return Ok(());
             }
 
-            let content_str = String::from_utf8(content)
-                .into_internal(format!("Invalid utf-8 in function body of '{name}'"))
+            self.expect_end_of_tag("set")?;
+
+            let content = self.parse_child(ParseUntil::EndSet)?;
+
+            self.context_mut().into_step()?.add_variable(variable, path, content);
+
+            Ok(())
+        }
+    }
+
+    fn parse_se(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_set()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_stat(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.buffer_all_until_end_of_self_closing_tag("stat")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+
+            Ok(())
+        }
+        else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
+            self.output_mut().into_step()?.clear_buffer();
+
+            let variable = self.parse_variable_name("stat")?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'stat' tag")));
+            }
+
+            self.tag_expect_char("stat", |c| matches!(c, 'o'))?;
+            self.tag_expect_char("stat", |c| matches!(c, 'f'))?;
+
+            if !self.bypass_whitespace_enforce_one()? {
+                return Err(Err(InternalError::new("Unexpected character in 'stat' tag")));
+            }
+
+            let path = self.parse_value_as_path("stat")?
+                .into_internal("Cannot stat a None path")
                 .into_step()?;
 
-            self.context_mut().into_step()?.add_function(name, args, content_str);
+            self.expect_end_of_self_close_tag("stat")?;
+
+            let stat = listing::stat(&path).into_step()?;
+            let input_path = self.input().into_step()?.path().to_owned();
+
+            self.context_mut().into_step()?.add_variable(variable, input_path, stat);
 
             Ok(())
         }
     }
 
-    fn parse_f(&mut self) -> StepResult<()> {
+    fn parse_sta(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'n' => {
+            't' => {
                 self.push_step()?;
-                self.parse_fn()
+                self.parse_stat()
             },
-            'o' => {
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_st(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
                 self.push_step()?;
-                self.parse_fo()
+                self.parse_sta()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_if(&mut self) -> StepResult<()> {
+    fn parse_sub(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unexpected_tag();
             }
 
-            self.buffer_all_until_sequence("if", &['%', '}'])?;
+            self.buffer_all_until_end_of_tag("sub")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndIf)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndSub)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
             match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndIf)
-                        .into_step()?;
-                    self.output_mut().into_step()?.write_bytes_to_buffer(else_content);
-                },
-                EndPosition::If => {},
+                EndPosition::Sub => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'if' tag, '{pos:?}'"
+                    "Invalid end position in 'sub' tag, '{pos:?}'"
                 )))),
             };
 
@@ -1837,159 +4875,108 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let condition = IfParser::parse_result("if", self)?;
+            let value = self.parse_value_as_number("sub")?;
 
-            self.output_mut().into_step()?.clear_buffer();
-            self.bypass_whitespace()?;
-            self.expect_end_of_tag("if")?;
+            self.expect_end_of_tag("sub")?;
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndIf)
-                .into_step()?;
-            let else_content = match end_position {
-                EndPosition::Else => {
-                    let (else_content, ..) = self.parse_bypassed(ParseUntil::EndIf)
-                        .into_step()?;
-                    Some(else_content)
-                },
-                EndPosition::If => None,
-                pos => return Err(Err(InternalError::new(format!("Invalid end position in 'if' tag, '{pos:?}'")))),
-            };
+            let content = self.parse_child_as_number(ParseUntil::EndSub)?;
 
-            // parse if block
-            if condition.as_evaluation() {
-                self.parse_limited(content.as_slice(), ParseUntil::EndIf).into_step()?;
-            }
-            // parse else block
-            else if let Some(else_content) = else_content {
-                self.parse_limited(else_content.as_slice(), ParseUntil::EndIf).into_step()?;
-            }
+            self.output_mut().into_step()?.write_str(&value.sub(content).into_step()?.to_string());
+            self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
     }
 
-    fn parse_include(&mut self) -> StepResult<()> {
+    fn parse_super(&mut self) -> StepResult<()> {
         if self.bypass() {
-            if !self.buffer_whitespace_enforce_one()? {
-                return self.unknown_tag();
-            }
-
-            self.buffer_all_until_end_of_self_closing_tag("include")?;
+            self.buffer_all_until_end_of_self_closing_tag("super")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
         }
         else {
-            if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
-            }
+            let state = match self.current_block.clone() {
+                Some(state) => state,
+                None => return Err(Err(InternalError::new("A 'super' tag must be within a block"))),
+            };
 
             self.output_mut().into_step()?.clear_buffer();
+            self.bypass_whitespace()?;
+            self.expect_end_of_self_close_tag("super")?;
 
-            let path = self.parse_value_as_path("include")?
-                .into_internal("The included path was None and this message needs improvement")
-                .into_step()?;
-            let file = OpenOptions::new()
-                .read(true)
-                .write(false)
-                .create(false)
-                .open(&path)
-                .into_internal(format!("Failed to open file {path:?}"))
-                .into_step()?;
-
-            self.output_mut().into_step()?.flush_buffer_and_file(file)
-                .into_step()?;
+            if state.index + 1 >= state.blocks.len() {
+                return Err(Err(InternalError::new(format!(
+                    "Block '{}' has no parent block to render", state.name
+                ))));
+            }
 
-            self.expect_end_of_self_close_tag("include")?;
+            self.parse_block(BlockState { index: state.index + 1, ..state }).into_step()?;
+            self.propagate_loop_flow()?;
 
             Ok(())
         }
     }
 
-    fn parse_includ(&mut self) -> StepResult<()> {
+    fn parse_supe(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'e' => {
+            'r' => {
                 self.push_step()?;
-                self.parse_include()
+                self.parse_super()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_inclu(&mut self) -> StepResult<()> {
+    fn parse_sup(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_includ()
+                self.parse_supe()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_incl(&mut self) -> StepResult<()> {
+    fn parse_su(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'u' => {
+            'b' => {
                 self.push_step()?;
-                self.parse_inclu()
+                self.parse_sub()
             },
-            _ => self.unexpected_tag(),
-        }
-    }
-
-    fn parse_inc(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            'p' => {
                 self.push_step()?;
-                self.parse_incl()
+                self.parse_sup()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_in(&mut self) -> StepResult<()> {
+    fn parse_s(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'c' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_inc()
+                self.parse_se()
             },
-            _ => self.unexpected_tag(),
-        }
-    }
-
-    fn parse_i(&mut self) -> StepResult<()> {
-        match self.current_or_unexpected_eof_in_tag()? {
-            'f' => {
+            't' => {
                 self.push_step()?;
-                self.parse_if()
+                self.parse_st()
             },
-            'n' => {
+            'u' => {
                 self.push_step()?;
-                self.parse_in()
+                self.parse_su()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_mod(&mut self) -> StepResult<()> {
+    fn parse_unset(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_tag();
             }
 
-            self.buffer_all_until_end_of_tag("mod")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndMod)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
-
-            match end_position {
-                EndPosition::Mod => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'mod' tag, '{pos:?}'"
-                )))),
-            };
-
+            self.buffer_all_until_end_of_self_closing_tag("unset")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
             Ok(())
@@ -2001,122 +4988,73 @@ where
 
             self.output_mut().into_step()?.clear_buffer();
 
-            let value = self.parse_value_as_number("mod")?;
-
-            self.expect_end_of_tag("mod")?;
+            let variable = self.parse_variable_name("unset")?;
 
-            let content = self.parse_child(ParseUntil::EndMod).into_step()?
-                .trim()
-                .parse::<i64>()
-                .into_internal("Failed to parse content as a number")
-                .into_step()?;
+            self.expect_end_of_self_close_tag("unset")?;
 
-            self.output_mut().into_step()?.write_str(&(value % content).to_string());
-            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.context_mut().into_step()?.unset_variable(variable);
 
             Ok(())
         }
     }
 
-    fn parse_mo(&mut self) -> StepResult<()> {
+    fn parse_unse(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
+            't' => {
                 self.push_step()?;
-                self.parse_mod()
+                self.parse_unset()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_mul(&mut self) -> StepResult<()> {
-        if self.bypass() {
-            if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
-            }
-
-            self.buffer_all_until_end_of_tag("mul")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndMul)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
-
-            match end_position {
-                EndPosition::Mul => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'mul' tag, '{pos:?}'"
-                )))),
-            };
-
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            Ok(())
-        }
-        else {
-            if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
-            }
-
-            self.output_mut().into_step()?.clear_buffer();
-
-            let value = self.parse_value_as_number("mul")?;
-
-            self.expect_end_of_tag("mul")?;
-
-            let content = self.parse_child(ParseUntil::EndMul).into_step()?
-                .trim()
-                .parse::<i64>()
-                .into_internal("Failed to parse content as a number")
-                .into_step()?;
-
-            self.output_mut().into_step()?.write_str(&(value * content).to_string());
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            Ok(())
+    fn parse_uns(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_unse()
+            },
+            _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_mu(&mut self) -> StepResult<()> {
+    fn parse_un(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            's' => {
                 self.push_step()?;
-                self.parse_mul()
+                self.parse_uns()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_m(&mut self) -> StepResult<()> {
+    fn parse_u(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'o' => {
-                self.push_step()?;
-                self.parse_mo()
-            },
-            'u' => {
+            'n' => {
                 self.push_step()?;
-                self.parse_mu()
+                self.parse_un()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_nth(&mut self) -> StepResult<()> {
+    fn parse_with(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_tag();
             }
 
-            self.buffer_all_until_end_of_tag("nth")?;
+            self.buffer_all_until_end_of_tag("with")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
 
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndNth)
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndWith)
                 .into_step()?;
             self.output_mut().into_step()?.write_bytes_to_buffer(content);
 
             match end_position {
-                EndPosition::Nth => {},
+                EndPosition::With => {},
                 pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'nth' tag, '{pos:?}'"
+                    "Invalid end position in 'with' tag, '{pos:?}'"
                 )))),
             };
 
@@ -2125,378 +5063,370 @@ where
             Ok(())
         }
         else {
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_tag();
+            }
+
             self.output_mut().into_step()?.clear_buffer();
-            self.bypass_whitespace()?;
 
-            let alias = self.parse_variable_name("nth")?;
+            let path = self.input().into_step()?.path().to_owned();
+            let mut depths = Vec::<(String, usize)>::new();
 
-            self.expect_end_of_tag("nth")?;
+            // each binding is added as it is parsed, so that a binding may
+            // refer to those before it
+            loop {
+                let variable = self.parse_variable_name("with")?;
 
-            let output = self.parse_child(ParseUntil::EndNth).into_step()?;
-            let values = self.context().into_step()?.values(&alias);
+                self.bypass_whitespace()?;
+                self.tag_expect_char("with", |c| matches!(c, '='))?;
+                self.bypass_whitespace()?;
 
-            let trimmed = output.trim();
+                let value = self.parse_value("with")?.unwrap_or(Value::Null);
 
-            let was_neg_zero;
-            let idx = if trimmed == "-0" {
-                was_neg_zero = true;
-                0
-            }
-            else {
-                was_neg_zero = false;
-                output.trim()
-                    .parse::<i64>()
-                    .map_err(|_| InternalError::new(format!(
-                        "Content was not an integer:\n{output}"
-                    )))
-                    .into_step()?
-            };
+                // the depth before the first binding of a variable is the
+                // one it is restored to
+                if !depths.iter().any(|(name, _)| name == &variable) {
+                    let depth = self.context().into_step()?.depth(&variable);
+                    depths.push((variable.to_owned(), depth));
+                }
 
-            let idx_to_take = if was_neg_zero || idx < 0_i64 {
-                values.as_ref().map_or(0_usize, |vs| vs.len()
-                    .saturating_sub(1)
-                    .saturating_sub((0 - idx) as usize)
-                )
+                self.context_mut().into_step()?.add_variable(&variable, &path, value);
+
+                self.bypass_whitespace()?;
+
+                if let Some(',') = self.current()? {
+                    self.push_step()?;
+                    self.bypass_whitespace()?;
+                }
+                else {
+                    break;
+                }
             }
-            else {
-                idx as usize
+
+            self.expect_end_of_tag("with")?;
+
+            let (content, end_position) = self.parse_bypassed(ParseUntil::EndWith)
+                .into_step()?;
+
+            match end_position {
+                EndPosition::With => {},
+                pos => return Err(Err(InternalError::new(format!(
+                    "Invalid end position in 'with' tag, '{pos:?}'"
+                )))),
             };
 
-            let value = values.and_then(
-                |vs| vs.into_iter().nth(idx_to_take).map(|v| v.to_owned())
-            );
+            self.parse_limited(content.as_slice(), ParseUntil::EndWith).into_step()?;
 
-            self.output_mut().into_step()?.write_str(&value.unwrap_or(String::new()));
-            self.output_mut().into_step()?.flush_buffer_to_content();
+            // anything stacked on a binding within the block, i.e. by a `set`
+            // tag, is discarded along with it
+            for (variable, depth) in depths {
+                self.context_mut().into_step()?.truncate_variable(variable, depth);
+            }
+
+            self.propagate_loop_flow()?;
 
             Ok(())
         }
     }
 
-    fn parse_nt(&mut self) -> StepResult<()> {
+    fn parse_wit(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             'h' => {
                 self.push_step()?;
-                self.parse_nth()
+                self.parse_with()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_n(&mut self) -> StepResult<()> {
+    fn parse_wi(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             't' => {
                 self.push_step()?;
-                self.parse_nt()
+                self.parse_wit()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_w(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_wi()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_path(&mut self) -> StepResult<()> {
+    fn parse_end_add(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unknown_end_tag();
             }
 
-            self.buffer_all_until_end_of_self_closing_tag("path")?;
+            match self.parse_until() {
+                ParseUntil::EndAdd => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag_buffer("add")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Add);
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
         else {
+            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unknown_end_tag();
             }
 
-            self.output_mut().into_step()?.clear_buffer();
-
-            let path = self.parse_value("path")?
-                .into_internal("Cannot canonicalize a path from an empty value")
-                .into_step()?;
-
-            self.bypass_whitespace()?;
-
-            let mut reldir = if self.tag_current_or_unexpected_eof("path")? == 'i' {
-                self.push_step()?;
-
-                self.tag_expect_char("path", |c| matches!(c, 'n'))?;
-                self.bypass_whitespace()?;
-
-                self.output_mut().into_step()?.clear_buffer();
-
-                self.parse_value_as_path("path")?
-                    .into_internal("Cannot create an absolute path from a None path")
-                    .into_step()?
+            match self.parse_until() {
+                ParseUntil::EndAdd => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
             }
-            else {
-                let mut input_path = self.input().into_step()?.path().to_owned();
-                input_path.pop();
-                input_path
-            };
-
-            reldir.push(path);
-
-            let dir = canonicalize(&reldir)
-                .into_internal(format!("Failed to canonicalize relative path {reldir:?}"))
-                .into_step()?;
-
-            self.bypass_whitespace()?;
-
-            self.output_mut().into_step()?.clear_buffer();
-            self.output_mut().into_step()?.write_str(dir.to_str().unwrap_or(""));
-            self.output_mut().into_step()?.flush_buffer_to_content();
 
-            self.expect_end_of_self_close_tag("path")?;
+            self.expect_end_of_end_tag("add")?;
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_pat(&mut self) -> StepResult<()> {
+    fn parse_end_ad(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'h' => {
+            'd' => {
                 self.push_step()?;
-                self.parse_path()
+                self.parse_end_add()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_pa(&mut self) -> StepResult<()> {
+    fn parse_end_a(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            't' => {
+            'd' => {
                 self.push_step()?;
-                self.parse_pat()
+                self.parse_end_ad()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_pow(&mut self) -> StepResult<()> {
+    fn parse_end_block(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_end_tag();
             }
 
-            self.buffer_all_until_end_of_tag("pow")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndPow)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
-
-            match end_position {
-                EndPosition::Pow => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'pow' tag, '{pos:?}'"
-                )))),
-            };
+            match self.parse_until() {
+                ParseUntil::EndBlock => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
 
+            self.expect_end_of_end_tag_buffer("block")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Block);
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
         else {
+            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unknown_end_tag();
             }
 
-            self.output_mut().into_step()?.clear_buffer();
-
-            let value = self.parse_value_as_number("pow")?;
-
-            self.expect_end_of_tag("pow")?;
-
-            let content = self.parse_child(ParseUntil::EndPow).into_step()?
-                .trim()
-                .parse::<i64>()
-                .into_internal("Failed to parse content as a number")
-                .into_step()?;
-
-            let val_u32: u32 = value.try_into()
-                .into_internal("Value was too large for 'pow' operations")
-                .into_step()?;
-            let con_u32: u32 = content.try_into()
-                .into_internal("Content produced too large a number for 'pow' operations")
-                .into_step()?;
-            let pow_u32 = val_u32.checked_pow(con_u32)
-                .into_internal("Value produced by 'pow' operation caused an overflow")
-                .into_step()?;
+            match self.parse_until() {
+                ParseUntil::EndBlock => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
 
-            self.output_mut().into_step()?.write_str(&pow_u32.to_string());
-            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.expect_end_of_end_tag("block")?;
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_po(&mut self) -> StepResult<()> {
+    fn parse_end_bloc(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'w' => {
+            'k' => {
                 self.push_step()?;
-                self.parse_pow()
+                self.parse_end_block()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_p(&mut self) -> StepResult<()> {
+    fn parse_end_blo(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'a' => {
+            'c' => {
                 self.push_step()?;
-                self.parse_pa()
+                self.parse_end_bloc()
             },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_bl(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
             'o' => {
                 self.push_step()?;
-                self.parse_po()
+                self.parse_end_blo()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_set(&mut self) -> StepResult<()> {
+    fn parse_end_b(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_end_bl()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_div(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_end_tag();
             }
 
-            self.buffer_all_until_end_of_tag("set")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndSet)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
-
-            match end_position {
-                EndPosition::Set => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'set' tag, '{pos:?}'"
-                )))),
-            };
+            match self.parse_until() {
+                ParseUntil::EndDiv => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
 
+            self.expect_end_of_end_tag_buffer("mul")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Div);
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
         else {
+            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unknown_end_tag();
             }
 
-            self.output_mut().into_step()?.clear_buffer();
-
-            let variable = self.parse_variable_name("set")?;
-
-            self.expect_end_of_tag("set")?;
+            match self.parse_until() {
+                ParseUntil::EndDiv => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
 
-            let content = self.parse_child(ParseUntil::EndSet).into_step()?;
-            let path = self.input().into_step()?.path().to_owned();
+            self.expect_end_of_end_tag("mul")?;
 
-            self.context_mut().into_step()?.add_variable(variable, path, content);
+            Err(Ok(FlowControl::Break))
+        }
+    }
 
-            Ok(())
+    fn parse_end_di(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'v' => {
+                self.push_step()?;
+                self.parse_end_div()
+            },
+            _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_se(&mut self) -> StepResult<()> {
+    fn parse_end_d(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            't' => {
+            'i' => {
                 self.push_step()?;
-                self.parse_set()
+                self.parse_end_di()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_sub(&mut self) -> StepResult<()> {
+    fn parse_end_fordir(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
-                return self.unexpected_tag();
+                return self.unknown_end_tag();
             }
 
-            self.buffer_all_until_end_of_tag("sub")?;
-            self.output_mut().into_step()?.flush_buffer_to_content();
-
-            let (content, end_position) = self.parse_bypassed(ParseUntil::EndSub)
-                .into_step()?;
-            self.output_mut().into_step()?.write_bytes_to_buffer(content);
-
-            match end_position {
-                EndPosition::Sub => {},
-                pos => return Err(Err(InternalError::new(format!(
-                    "Invalid end position in 'sub' tag, '{pos:?}'"
-                )))),
-            };
+            match self.parse_until() {
+                ParseUntil::EndFordir => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
 
+            self.expect_end_of_end_tag_buffer("fordir")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Fordir);
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
         else {
+            self.output_mut().into_step()?.clear_buffer();
             if !self.bypass_whitespace_enforce_one()? {
-                return self.unknown_tag();
+                return self.unknown_end_tag();
             }
 
-            self.output_mut().into_step()?.clear_buffer();
-
-            let value = self.parse_value_as_number("sub")?;
-
-            self.expect_end_of_tag("sub")?;
-
-            let content = self.parse_child(ParseUntil::EndSub).into_step()?
-                .trim()
-                .parse::<i64>()
-                .into_internal("Failed to parse content as a number")
-                .into_step()?;
+            match self.parse_until() {
+                ParseUntil::EndFordir => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
 
-            self.output_mut().into_step()?.write_str(&(value - content).to_string());
-            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.expect_end_of_end_tag("fordir")?;
 
-            Ok(())
+            Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_su(&mut self) -> StepResult<()> {
+    fn parse_end_fordi(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'b' => {
+            'r' => {
                 self.push_step()?;
-                self.parse_sub()
+                self.parse_end_fordir()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_s(&mut self) -> StepResult<()> {
+    fn parse_end_ford(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'e' => {
-                self.push_step()?;
-                self.parse_se()
-            },
-            'u' => {
+            'i' => {
                 self.push_step()?;
-                self.parse_su()
+                self.parse_end_fordi()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_add(&mut self) -> StepResult<()> {
+    fn parse_end_foreach(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_end_tag();
             }
 
             match self.parse_until() {
-                ParseUntil::EndAdd => {},
+                ParseUntil::EndForeach => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag_buffer("add")?;
+            self.expect_end_of_end_tag_buffer("foreach")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.set_end_position(EndPosition::Add);
+            self.set_end_position(EndPosition::Foreach);
 
             Err(Ok(FlowControl::Break))
         }
@@ -2507,54 +5437,64 @@ where
             }
 
             match self.parse_until() {
-                ParseUntil::EndAdd => {},
+                ParseUntil::EndForeach => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag("add")?;
+            self.expect_end_of_end_tag("foreach")?;
 
             Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_end_ad(&mut self) -> StepResult<()> {
+    fn parse_end_foreac(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
+            'h' => {
                 self.push_step()?;
-                self.parse_end_add()
+                self.parse_end_foreach()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_a(&mut self) -> StepResult<()> {
+    fn parse_end_forea(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'd' => {
+            'c' => {
                 self.push_step()?;
-                self.parse_end_ad()
+                self.parse_end_foreac()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_div(&mut self) -> StepResult<()> {
+    fn parse_end_fore(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_end_forea()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_forfile(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_end_tag();
             }
 
             match self.parse_until() {
-                ParseUntil::EndDiv => {},
+                ParseUntil::EndForfile => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag_buffer("mul")?;
+            self.expect_end_of_end_tag_buffer("forfile")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.set_end_position(EndPosition::Div);
+            self.set_end_position(EndPosition::Forfile);
 
             Err(Ok(FlowControl::Break))
         }
@@ -2565,54 +5505,64 @@ where
             }
 
             match self.parse_until() {
-                ParseUntil::EndDiv => {},
+                ParseUntil::EndForfile => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag("mul")?;
+            self.expect_end_of_end_tag("forfile")?;
 
             Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_end_di(&mut self) -> StepResult<()> {
+    fn parse_end_forfil(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'v' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_end_div()
+                self.parse_end_forfile()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_d(&mut self) -> StepResult<()> {
+    fn parse_end_forfi(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'l' => {
+                self.push_step()?;
+                self.parse_end_forfil()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_forf(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             'i' => {
                 self.push_step()?;
-                self.parse_end_di()
+                self.parse_end_forfi()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_fordir(&mut self) -> StepResult<()> {
+    fn parse_end_forkeys(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_end_tag();
             }
 
             match self.parse_until() {
-                ParseUntil::EndFordir => {},
+                ParseUntil::EndForkeys => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag_buffer("fordir")?;
+            self.expect_end_of_end_tag_buffer("forkeys")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.set_end_position(EndPosition::Fordir);
+            self.set_end_position(EndPosition::Forkeys);
 
             Err(Ok(FlowControl::Break))
         }
@@ -2623,54 +5573,64 @@ where
             }
 
             match self.parse_until() {
-                ParseUntil::EndFordir => {},
+                ParseUntil::EndForkeys => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag("fordir")?;
+            self.expect_end_of_end_tag("forkeys")?;
 
             Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_end_fordi(&mut self) -> StepResult<()> {
+    fn parse_end_forkey(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'r' => {
+            's' => {
                 self.push_step()?;
-                self.parse_end_fordir()
+                self.parse_end_forkeys()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_ford(&mut self) -> StepResult<()> {
+    fn parse_end_forke(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'y' => {
                 self.push_step()?;
-                self.parse_end_fordi()
+                self.parse_end_forkey()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_foreach(&mut self) -> StepResult<()> {
+    fn parse_end_fork(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_end_forke()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_forline(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_end_tag();
             }
 
             match self.parse_until() {
-                ParseUntil::EndForeach => {},
+                ParseUntil::EndForline => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag_buffer("foreach")?;
+            self.expect_end_of_end_tag_buffer("forline")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.set_end_position(EndPosition::Foreach);
+            self.set_end_position(EndPosition::Forline);
 
             Err(Ok(FlowControl::Break))
         }
@@ -2681,64 +5641,64 @@ where
             }
 
             match self.parse_until() {
-                ParseUntil::EndForeach => {},
+                ParseUntil::EndForline => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag("foreach")?;
+            self.expect_end_of_end_tag("forline")?;
 
             Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_end_foreac(&mut self) -> StepResult<()> {
+    fn parse_end_forlin(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'h' => {
+            'e' => {
                 self.push_step()?;
-                self.parse_end_foreach()
+                self.parse_end_forline()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_forea(&mut self) -> StepResult<()> {
+    fn parse_end_forli(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'c' => {
+            'n' => {
                 self.push_step()?;
-                self.parse_end_foreac()
+                self.parse_end_forlin()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_fore(&mut self) -> StepResult<()> {
+    fn parse_end_forl(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'a' => {
+            'i' => {
                 self.push_step()?;
-                self.parse_end_forea()
+                self.parse_end_forli()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_forfile(&mut self) -> StepResult<()> {
+    fn parse_end_forrange(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_end_tag();
             }
 
             match self.parse_until() {
-                ParseUntil::EndForfile => {},
+                ParseUntil::EndForrange => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag_buffer("forfile")?;
+            self.expect_end_of_end_tag_buffer("forrange")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.set_end_position(EndPosition::Forfile);
+            self.set_end_position(EndPosition::Forrange);
 
             Err(Ok(FlowControl::Break))
         }
@@ -2749,43 +5709,53 @@ where
             }
 
             match self.parse_until() {
-                ParseUntil::EndForfile => {},
+                ParseUntil::EndForrange => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag("forfile")?;
+            self.expect_end_of_end_tag("forrange")?;
 
             Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_end_forfil(&mut self) -> StepResult<()> {
+    fn parse_end_forrang(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
             'e' => {
                 self.push_step()?;
-                self.parse_end_forfile()
+                self.parse_end_forrange()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_forfi(&mut self) -> StepResult<()> {
+    fn parse_end_forran(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'l' => {
+            'g' => {
                 self.push_step()?;
-                self.parse_end_forfil()
+                self.parse_end_forrang()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_forf(&mut self) -> StepResult<()> {
+    fn parse_end_forra(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'i' => {
+            'n' => {
                 self.push_step()?;
-                self.parse_end_forfi()
+                self.parse_end_forran()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_forr(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_end_forra()
             },
             _ => self.unexpected_tag(),
         }
@@ -2883,6 +5853,18 @@ where
                 self.push_step()?;
                 self.parse_end_forf()
             },
+            'k' => {
+                self.push_step()?;
+                self.parse_end_fork()
+            },
+            'l' => {
+                self.push_step()?;
+                self.parse_end_forl()
+            },
+            'r' => {
+                self.push_step()?;
+                self.parse_end_forr()
+            },
             's' => {
                 self.push_step()?;
                 self.parse_end_fors()
@@ -2935,40 +5917,170 @@ where
 
             self.expect_end_of_end_tag("fn")?;
 
-            Err(Ok(FlowControl::Break))
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_end_f(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'n' => {
+                self.push_step()?;
+                self.parse_end_fn()
+            },
+            'o' => {
+                self.push_step()?;
+                self.parse_end_fo()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_if(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndIf => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag_buffer("if")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::If);
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndIf => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag("if")?;
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_end_invoke(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndInvoke => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag_buffer("invoke")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Invoke);
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndInvoke => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag("invoke")?;
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_end_invok(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_end_invoke()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_invo(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'k' => {
+                self.push_step()?;
+                self.parse_end_invok()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_inv(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'o' => {
+                self.push_step()?;
+                self.parse_end_invo()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_in(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'v' => {
+                self.push_step()?;
+                self.parse_end_inv()
+            },
+            _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_f(&mut self) -> StepResult<()> {
+    fn parse_end_i(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'n' => {
+            'f' => {
                 self.push_step()?;
-                self.parse_end_fn()
+                self.parse_end_if()
             },
-            'o' => {
+            'n' => {
                 self.push_step()?;
-                self.parse_end_fo()
+                self.parse_end_in()
             },
             _ => self.unexpected_tag(),
         }
     }
 
-    fn parse_end_if(&mut self) -> StepResult<()> {
+    fn parse_end_match(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
                 return self.unknown_end_tag();
             }
 
             match self.parse_until() {
-                ParseUntil::EndIf => {},
+                ParseUntil::EndMatch => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag_buffer("if")?;
+            self.expect_end_of_end_tag_buffer("match")?;
             self.output_mut().into_step()?.flush_buffer_to_content();
-            self.set_end_position(EndPosition::If);
+            self.set_end_position(EndPosition::Match);
 
             Err(Ok(FlowControl::Break))
         }
@@ -2979,23 +6091,43 @@ where
             }
 
             match self.parse_until() {
-                ParseUntil::EndIf => {},
+                ParseUntil::EndMatch => {},
                 _ => {
                     return self.unexpected_end_tag();
                 },
             }
 
-            self.expect_end_of_end_tag("if")?;
+            self.expect_end_of_end_tag("match")?;
 
             Err(Ok(FlowControl::Break))
         }
     }
 
-    fn parse_end_i(&mut self) -> StepResult<()> {
+    fn parse_end_matc(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
-            'f' => {
+            'h' => {
                 self.push_step()?;
-                self.parse_end_if()
+                self.parse_end_match()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_mat(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'c' => {
+                self.push_step()?;
+                self.parse_end_matc()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_ma(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_end_mat()
             },
             _ => self.unexpected_tag(),
         }
@@ -3098,6 +6230,10 @@ where
 
     fn parse_end_m(&mut self) -> StepResult<()> {
         match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_end_ma()
+            },
             'o' => {
                 self.push_step()?;
                 self.parse_end_mo()
@@ -3225,6 +6361,142 @@ where
         }
     }
 
+    fn parse_end_query(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndQuery => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag_buffer("query")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Query);
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndQuery => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag("query")?;
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_end_quer(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'y' => {
+                self.push_step()?;
+                self.parse_end_query()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_que(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'r' => {
+                self.push_step()?;
+                self.parse_end_quer()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_qu(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'e' => {
+                self.push_step()?;
+                self.parse_end_que()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_q(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'u' => {
+                self.push_step()?;
+                self.parse_end_qu()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_raw(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndRaw => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag_buffer("raw")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::Raw);
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndRaw => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag("raw")?;
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_end_ra(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'w' => {
+                self.push_step()?;
+                self.parse_end_raw()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_r(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'a' => {
+                self.push_step()?;
+                self.parse_end_ra()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
     fn parse_end_set(&mut self) -> StepResult<()> {
         if self.bypass() {
             if !self.buffer_whitespace_enforce_one()? {
@@ -3335,6 +6607,74 @@ where
         }
     }
 
+    fn parse_end_with(&mut self) -> StepResult<()> {
+        if self.bypass() {
+            if !self.buffer_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndWith => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag_buffer("with")?;
+            self.output_mut().into_step()?.flush_buffer_to_content();
+            self.set_end_position(EndPosition::With);
+
+            Err(Ok(FlowControl::Break))
+        }
+        else {
+            self.output_mut().into_step()?.clear_buffer();
+            if !self.bypass_whitespace_enforce_one()? {
+                return self.unknown_end_tag();
+            }
+
+            match self.parse_until() {
+                ParseUntil::EndWith => {},
+                _ => {
+                    return self.unexpected_end_tag();
+                },
+            }
+
+            self.expect_end_of_end_tag("with")?;
+
+            Err(Ok(FlowControl::Break))
+        }
+    }
+
+    fn parse_end_wit(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'h' => {
+                self.push_step()?;
+                self.parse_end_with()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_wi(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            't' => {
+                self.push_step()?;
+                self.parse_end_wit()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
+    fn parse_end_w(&mut self) -> StepResult<()> {
+        match self.current_or_unexpected_eof_in_tag()? {
+            'i' => {
+                self.push_step()?;
+                self.parse_end_wi()
+            },
+            _ => self.unexpected_tag(),
+        }
+    }
+
     fn parse_end(&mut self) -> StepResult<()> {
         if let ParseUntil::Eof = self.parse_until() {
             return self.unexpected_end_tag();
@@ -3353,6 +6693,10 @@ where
                 self.push_step()?;
                 self.parse_end_a()
             },
+            'b' => {
+                self.push_step()?;
+                self.parse_end_b()
+            },
             'd' => {
                 self.push_step()?;
                 self.parse_end_d()
@@ -3377,10 +6721,22 @@ where
                 self.push_step()?;
                 self.parse_end_p()
             },
+            'q' => {
+                self.push_step()?;
+                self.parse_end_q()
+            },
+            'r' => {
+                self.push_step()?;
+                self.parse_end_r()
+            },
             's' => {
                 self.push_step()?;
                 self.parse_end_s()
             },
+            'w' => {
+                self.push_step()?;
+                self.parse_end_w()
+            },
             _ => self.unknown_end_tag(),
         }
     }
@@ -3399,6 +6755,10 @@ where
                 self.push_step()?;
                 self.parse_a()
             },
+            'b' => {
+                self.push_step()?;
+                self.parse_b()
+            },
             'c' => {
                 self.push_step()?;
                 self.parse_c()
@@ -3419,6 +6779,10 @@ where
                 self.push_step()?;
                 self.parse_i()
             },
+            'l' => {
+                self.push_step()?;
+                self.parse_l()
+            },
             'm' => {
                 self.push_step()?;
                 self.parse_m()
@@ -3431,10 +6795,26 @@ where
                 self.push_step()?;
                 self.parse_p()
             },
+            'q' => {
+                self.push_step()?;
+                self.parse_q()
+            },
+            'r' => {
+                self.push_step()?;
+                self.parse_r()
+            },
             's' => {
                 self.push_step()?;
                 self.parse_s()
             },
+            'u' => {
+                self.push_step()?;
+                self.parse_u()
+            },
+            'w' => {
+                self.push_step()?;
+                self.parse_w()
+            },
             '/' => {
                 self.push_step()?;
                 self.parse_end()
@@ -3504,34 +6884,34 @@ where
 
             match self.current_or_unexpected_eof_in_tag()? {
                 '(' => {
-                    let function = self.context().into_step()?.function(&alias)
-                        .into_internal(format!("Function '{alias}' never defined"))
-                        .into_step()?
-                        .to_owned();
-
-                    let mut args = self.parse_function_arg_values("exec")?
-                        .into_iter();
-                    let mut ctx = self.context().into_step()?.to_owned();
+                    let args = self.parse_function_call_args("exec")?;
+                    let filters = self.parse_filters("output")?;
 
-                    for named in function.args().iter() {
-                        ctx.remove_variable(named);
-
-                        if let Some(Some(arg)) = args.next() {
-                            ctx.add_variable(named, self.input().into_step()?
-                                .path(), &arg);
-                        }
+                    if filters.is_empty() {
+                        self.parse_function(&alias, args, None).into_step()?;
                     }
+                    else {
+                        let output = self.render_function(&alias, args).into_step()?;
+                        let output = Value::from(output)
+                            .to_output(&filters)
+                            .into_step()?;
 
-                    // make sure we write all buffered content before spawning the sealed
-                    // parser
-                    self.output_mut().into_step()?.flush_buffer_to_content();
-                    self.parse_limited_sealed(ctx, function.as_bytes(), ParseUntil::EndFn)
-                        .into_step()?;
+                        self.output_mut().into_step()?.write_str(&output);
+                        self.output_mut().into_step()?.flush_buffer_to_content();
+                    }
                 },
                 _ => {
-                    let value = self.context().into_step()?.value(&alias);
+                    let filters = self.parse_filters("output")?;
+
+                    let context = self.context().into_step()?;
+                    let mut output = context.object(&alias).as_ref()
+                        .to_output(&filters)
+                        .into_step()?;
+
+                    if !context.is_trusted(&alias) && !filters.iter().any(Filter::is_escaped) {
+                        output = context.escape().apply(output);
+                    }
 
-                    let output = value.map_or(String::new(), |v| v.to_owned());
                     self.output_mut().into_step()?.write_str(&output);
                     self.output_mut().into_step()?.flush_buffer_to_content();
                 },
@@ -3666,3 +7046,30 @@ where
         Ok(())
     }
 }
+
+/// Whether `content` holds nothing but whitespace, escaped whitespace and
+/// comments.
+fn is_blank(content: &[u8]) -> bool {
+    let mut rest = content;
+
+    loop {
+        rest = match rest {
+            [] => return true,
+            [b'{', b'#', comment @ ..] => {
+                let mut comment = comment;
+
+                loop {
+                    comment = match comment {
+                        [] => return false,
+                        [b'#', b'}', after @ ..] => break after,
+                        [b'\\', _, after @ ..] => after,
+                        [_, after @ ..] => after,
+                    };
+                }
+            },
+            [b'\\', c, after @ ..] if c.is_ascii_whitespace() => after,
+            [c, after @ ..] if c.is_ascii_whitespace() => after,
+            _ => return false,
+        };
+    }
+}
@@ -1,17 +1,21 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod test;
 
 use {
     crate::{
+        context::{ Arg, Param, },
         error::{
             InternalError,
             InternalResult,
             IntoInternal,
             OrElseUpgrade,
         },
+        filter::Filter,
         input::Input,
         macros::*,
         parser::Parser,
+        value::{ Number, ToOutput, Value, },
     },
     std::{
         fmt::Debug,
@@ -88,8 +92,6 @@ macro_rules! flow {
     }
 }
 
-pub(crate) use flow;
-
 macro_rules! flow_internal {
     ($to_result:expr) => {
         match $to_result {
@@ -664,29 +666,80 @@ where
         Ok(self.input().into_step()?.join_path(path))
     }
 
-    fn parse_variable<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<String>> {
+    fn parse_number<S>(&mut self, tagname: S) -> StepResult<Value>
+    where
+        S: AsRef<str>,
+    {
+        self.output_mut().into_step()?.clear_buffer();
+
+        if self.tag_current_or_unexpected_eof(tagname.as_ref())? == '-' {
+            self.push_step()?;
+        }
+
+        self.tag_expect_buffer_char(tagname.as_ref(), |c| matches!(c, number_chars!()))?;
+        self.buffer_all_while(|c| matches!(c, number_chars!()))?;
+
+        let is_real = if let Some('.') = self.current()? {
+            self.push_step()?;
+            self.tag_expect_buffer_char(tagname.as_ref(), |c| matches!(c, number_chars!()))?;
+            self.buffer_all_while(|c| matches!(c, number_chars!()))?;
+
+            true
+        }
+        else {
+            false
+        };
+
+        let number = String::from_utf8(self.output_mut().into_step()?.take_buffer())
+            .into_internal("Invalid utf8 in number")
+            .into_step()?;
+
+        if is_real {
+            number.parse::<f64>()
+                .map(Value::from)
+                .into_internal(format!("Failed to parse '{number}' as a real number"))
+                .into_step()
+        }
+        else {
+            number.parse::<i64>()
+                .map(Value::from)
+                .into_internal(format!("Failed to parse '{number}' as an integer"))
+                .into_step()
+        }
+    }
+
+    fn parse_variable<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<Value>> {
         let alias = self.parse_variable_name(tagname)?;
 
-        Ok(self.context().into_step()?.value(&alias).map(|v| v.to_owned()))
+        Ok(self.context().into_step()?.object(&alias))
     }
 
     fn parse_variable_as_path<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<PathBuf>> {
         let alias = self.parse_variable_name(tagname)?;
 
-        Ok(self.context().into_step()?.path(&alias))
+        self.context().into_step()?.path(&alias).into_step()
     }
 
-    fn parse_value<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<String>> {
+    fn parse_value<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<Value>> {
         let c = self.tag_current_or_unexpected_eof(tagname.as_ref())?;
 
         match c {
             // string
-            '"' => Ok(Some(self.parse_text(tagname)?)),
+            '"' => Ok(Some(Value::from(self.parse_text(tagname)?))),
+            // number
+            '-'|number_chars!() => Ok(Some(self.parse_number(tagname)?)),
             // variable
             _ => self.parse_variable(tagname),
         }
      }
 
+    fn parse_value_as_string<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<String>> {
+        match self.parse_value(tagname)? {
+            Some(value) => Ok(Some(value.to_output(&[]).into_step()?)),
+            None => Ok(None),
+        }
+     }
+
     fn parse_value_as_path<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Option<PathBuf>> {
         let c = self.tag_current_or_unexpected_eof(tagname.as_ref())?;
 
@@ -698,22 +751,21 @@ where
         }
      }
 
-    fn parse_value_as_number<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<i64> {
+    fn parse_value_as_number<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Number> {
         self.parse_value(tagname)?
             .into_internal("Cannot coerce an empty value into a number")
             .into_step()?
-            .trim()
-            .parse::<i64>()
+            .as_number()
             .into_internal("Failed to coerce value into a number")
             .into_step()
      }
 
-    fn parse_function_args<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Vec<String>> {
+    fn parse_function_args<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Vec<Param>> {
         self.bypass_whitespace()?;
         self.tag_expect_char(tagname.as_ref(), |c| matches!(c, '('))?;
         self.output_mut().into_step()?.clear_buffer();
 
-        let mut args = Vec::new();
+        let mut params: Vec<Param> = Vec::new();
         let mut first = true;
         while self.tag_current_or_unexpected_eof(tagname.as_ref())? != ')' {
             self.bypass_whitespace()?;
@@ -726,7 +778,93 @@ where
                 self.bypass_whitespace()?;
             }
 
-            args.push(self.parse_variable_name(tagname.as_ref())?);
+            if let Some(Param::Variadic(name)) = params.last() {
+                return Err(Err(InternalError::new(format!(
+                    "Variadic argument '{name}' must be the last argument"
+                ))));
+            }
+
+            let variadic = self.tag_current_or_unexpected_eof(tagname.as_ref())? == '*';
+            if variadic {
+                self.push_step()?;
+            }
+
+            let name = self.parse_variable_name(tagname.as_ref())?;
+            self.bypass_whitespace()?;
+
+            if params.iter().any(|param| param.name() == name) {
+                return Err(Err(InternalError::new(format!("Duplicate argument '{name}'"))));
+            }
+
+            let param = if variadic {
+                Param::Variadic(name)
+            }
+            else if self.tag_current_or_unexpected_eof(tagname.as_ref())? == '=' {
+                self.push_step()?;
+                self.bypass_whitespace()?;
+
+                let default = self.parse_value(tagname.as_ref())?.unwrap_or_default();
+                self.bypass_whitespace()?;
+
+                Param::Default(name, default)
+            }
+            else {
+                Param::Required(name)
+            };
+
+            params.push(param);
+        }
+
+        self.tag_expect_char(tagname.as_ref(), |c| matches!(c, ')'))?;
+
+        Ok(params)
+    }
+
+    /// Parses the arguments of a function call, where each is either a value
+    /// or a keyword argument, i.e. `title="X"`.
+    fn parse_function_call_args<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Vec<Arg>> {
+        self.bypass_whitespace()?;
+        self.tag_expect_char(tagname.as_ref(), |c| matches!(c, '('))?;
+        self.output_mut().into_step()?.clear_buffer();
+
+        let mut args: Vec<Arg> = Vec::new();
+        let mut first = true;
+        while self.tag_current_or_unexpected_eof(tagname.as_ref())? != ')' {
+            self.bypass_whitespace()?;
+
+            if first {
+                first = false;
+            }
+            else {
+                self.tag_expect_char(tagname.as_ref(), |c| matches!(c, ','))?;
+                self.bypass_whitespace()?;
+            }
+
+            let arg = match self.tag_current_or_unexpected_eof(tagname.as_ref())? {
+                '"'|'-'|number_chars!() => Arg { name: None, value: self.parse_value(tagname.as_ref())?, },
+                _ => {
+                    let name = self.parse_variable_name(tagname.as_ref())?;
+                    self.bypass_whitespace()?;
+
+                    if self.tag_current_or_unexpected_eof(tagname.as_ref())? == '=' {
+                        self.push_step()?;
+                        self.bypass_whitespace()?;
+
+                        Arg { name: Some(name), value: self.parse_value(tagname.as_ref())?, }
+                    }
+                    else {
+                        Arg { name: None, value: self.context().into_step()?.object(&name), }
+                    }
+                },
+            };
+
+            if arg.name.is_none() && args.iter().any(|arg| arg.name.is_some()) {
+                return Err(Err(InternalError::new(
+                    "A positional argument cannot follow a keyword argument"
+                )));
+            }
+
+            args.push(arg);
             self.bypass_whitespace()?;
         }
 
@@ -735,7 +873,7 @@ where
         Ok(args)
     }
 
-    fn parse_function_arg_values<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Vec<Option<String>>> {
+    fn parse_function_arg_values<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Vec<Option<Value>>> {
         self.bypass_whitespace()?;
         self.tag_expect_char(tagname.as_ref(), |c| matches!(c, '('))?;
         self.output_mut().into_step()?.clear_buffer();
@@ -761,6 +899,38 @@ where
 
         Ok(args)
     }
+
+    fn parse_filters<S: AsRef<str>>(&mut self, tagname: S) -> StepResult<Vec<Filter>> {
+        let mut filters = Vec::new();
+
+        loop {
+            self.bypass_whitespace()?;
+
+            if self.tag_current_or_unexpected_eof(tagname.as_ref())? != '|' {
+                break;
+            }
+
+            self.tag_expect_char(tagname.as_ref(), |c| matches!(c, '|'))?;
+
+            let name = self.parse_variable_name(tagname.as_ref())?;
+
+            self.bypass_whitespace()?;
+
+            let args = if self.tag_current_or_unexpected_eof(tagname.as_ref())? == '(' {
+                self.parse_function_arg_values(tagname.as_ref())?
+                    .into_iter()
+                    .map(Option::unwrap_or_default)
+                    .collect::<Vec<Value>>()
+            }
+            else {
+                Vec::new()
+            };
+
+            filters.push(Filter::new(&name, args).into_step()?);
+        }
+
+        Ok(filters)
+    }
 }
 
 impl<R, W, P> Steps<R, W> for P
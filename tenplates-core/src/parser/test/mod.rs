@@ -1,7 +1,9 @@
 use {
     crate::{
         context::Context,
+        filter::Escape,
         parser::{ Parser, TemplateParser },
+        value::Value,
     },
     std::path::PathBuf,
 };
@@ -142,6 +144,19 @@ fn parse_include_2() {
     assert_eq!("The name of the item is \"Item.\"", output_str);
 }
 
+#[test]
+fn parse_include_3() {
+    let input = "{% include \"./resources/parse_include_3/data.yaml\" /%}\n{% include \"./resources/parse_include_3/open.yaml\" /%}";
+    let mut output = Vec::<u8>::new();
+    let mut parser = TemplateParser::new(Context::default(), input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!("---\na: 1\n---\nb: 2\n---\nc: 3", output);
+}
+
 #[test]
 fn parse_comment_1() {
     let input = "Here is some text{# and a comment #} and some more text.";
@@ -434,6 +449,84 @@ fn parse_if_mixed_1() {
     assert_eq!("True", output_str);
 }
 
+#[test]
+fn parse_if_not_1() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("a", "./", "0");
+    let input = r#"{% if !a %}True{% else %}False{% /if %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("True", output_str);
+}
+
+#[test]
+fn parse_if_not_2() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("a", "./", "1");
+    context.add_variable("b", "./", "0");
+    let input = r#"{% if !(b || a) %}True{% else %}False{% /if %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("False", output_str);
+}
+
+#[test]
+fn parse_if_not_3() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("a", "./", "1");
+    context.add_variable("b", "./", "0");
+    let input = r#"{% if b || !a == "0" %}True{% else %}False{% /if %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("True", output_str);
+}
+
+#[test]
+fn parse_if_elif_1() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "b");
+    let input = r#"{% if kind == "a" %}A{% elif kind == "b" %}B{% elif kind == "c" %}C{% else %}D{% /if %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("B", output_str);
+}
+
+#[test]
+fn parse_if_elif_2() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "e");
+    let input = r#"{% if kind == "a" %}A{% elif kind == "b" %}B{% else %}D{% /if %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("D", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_if_elif_3() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "e");
+    let input = r#"{% if kind == "a" %}A{% else %}D{% elif kind == "b" %}B{% /if %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+}
+
 #[test]
 fn nested_parse_if_and_for() {
     let mut output = Vec::<u8>::new();
@@ -492,7 +585,7 @@ fn parse_complex_2() {
         &mut output
     ).unwrap();
     parser.parse().unwrap();
-    let split = parser.context().unwrap().value(&"split");
+    let split = parser.context().unwrap().value("split");
     assert_eq!(None, split);
     drop(parser);
 
@@ -878,6 +971,20 @@ fn parse_add_3() {
 	assert_eq!("4", output_str);
 }
 
+#[test]
+fn parse_add_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_add_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("3.25", output_str);
+}
+
 #[test]
 fn parse_sub_1() {
 	let mut output = Vec::<u8>::new();
@@ -962,6 +1069,18 @@ fn parse_mul_3() {
 	assert_eq!("38", output_str);
 }
 
+#[test]
+#[should_panic]
+fn parse_mul_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_mul_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
 #[test]
 fn parse_div_1() {
 	let mut output = Vec::<u8>::new();
@@ -1004,6 +1123,32 @@ fn parse_div_3() {
 	assert_eq!("3", output_str);
 }
 
+#[test]
+fn parse_div_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_div_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("3.8", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_div_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_div_5/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
 #[test]
 fn parse_mod_1() {
 	let mut output = Vec::<u8>::new();
@@ -1046,6 +1191,20 @@ fn parse_mod_3() {
 	assert_eq!("0", output_str);
 }
 
+#[test]
+fn parse_mod_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_mod_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1.5", output_str);
+}
+
 #[test]
 fn parse_pow_1() {
 	let mut output = Vec::<u8>::new();
@@ -1088,6 +1247,32 @@ fn parse_pow_3() {
 	assert_eq!("27", output_str);
 }
 
+#[test]
+fn parse_pow_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_pow_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("0.25", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_pow_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_pow_5/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
 #[test]
 fn parse_math_1() {
 	let mut output = Vec::<u8>::new();
@@ -1127,14 +1312,9 @@ fn parse_fordir_1() {
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
+	let dir = PathBuf::from("./resources/parse_fordir_1").canonicalize().unwrap();
 	assert_eq!(
-        concat!(
-            "./resources/parse_fordir_1/./4, ",
-            "./resources/parse_fordir_1/./3, ",
-            "./resources/parse_fordir_1/./2, ",
-            "./resources/parse_fordir_1/./1, ",
-            "./resources/parse_fordir_1/./0",
-        ),
+        format!("{0}/4, {0}/3, {0}/2, {0}/1, {0}/0", dir.display()),
         output_str
     );
 }
@@ -1164,14 +1344,9 @@ fn parse_forfile_1() {
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
+	let dir = PathBuf::from("./resources/parse_forfile_1/files").canonicalize().unwrap();
 	assert_eq!(
-        concat!(
-            "./resources/parse_forfile_1/./files/4, ",
-            "./resources/parse_forfile_1/./files/3, ",
-            "./resources/parse_forfile_1/./files/2, ",
-            "./resources/parse_forfile_1/./files/1, ",
-            "./resources/parse_forfile_1/./files/0",
-        ),
+        format!("{0}/4, {0}/3, {0}/2, {0}/1, {0}/0", dir.display()),
         output_str
     );
 }
@@ -1187,8 +1362,9 @@ fn parse_if_1() {
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
+	let dir = PathBuf::from("./resources/parse_if_1/testdir").canonicalize().unwrap();
 	assert_eq!(
-        "./resources/parse_if_1/./testdir/5",
+        format!("{}/5", dir.display()),
         output_str
     );
 }
@@ -1211,170 +1387,1325 @@ fn parse_if_2() {
 }
 
 #[test]
-fn parse_fn_call_1() {
+fn parse_if_elif_4() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-        concat!(
-            "{% set fnpath %}{% path \"./resources/parse_fn_call_1/functions/testobj.tenplate\" /%}{% /set %}",
-            "{% call fnpath /%}",
-            "{{ test.pagesize() }}\\",
-        ),
+		PathBuf::from("./resources/parse_if_elif_4/file.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "10",
-        output_str
-    );
+	assert_eq!("Nested: 2, 3", output_str);
 }
 
 #[test]
-fn parse_fn_call_2() {
+fn parse_match_1() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_fn_call_2/test.tenplate"),
+		PathBuf::from("./resources/parse_match_1/file.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "<h4 id=\"the_id\">Some text</h4>",
-        output_str
-    );
+	assert_eq!("Second, Third, Other", output_str);
 }
 
 #[test]
-fn parse_forsplit_1() {
+fn parse_match_2() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_forsplit_1/test.tenplate"),
+		PathBuf::from("./resources/parse_match_2/file.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "0, 1, 2, 3, 4, 5",
-        output_str
-    );
+	assert_eq!("Status: not found", output_str);
 }
 
 #[test]
-fn parse_forsplit_2() {
+fn parse_match_3() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "a");
+    let input = r#"{% match kind %}{% case "b" %}B{% /match %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_match_4() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "a");
+    let input = r#"{% match kind %}{% default %}D{% case "a" %}A{% /match %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+}
+
+#[test]
+#[should_panic]
+fn parse_match_5() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "a");
+    let input = r#"{% match kind %}text{% case "a" %}A{% /match %}"#;
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+}
+
+#[test]
+fn parse_match_6() {
+    let mut output = Vec::<u8>::new();
+    let mut context = Context::default();
+    context.add_variable("kind", "./", "a");
+    let input = "{% match kind %}{# the kinds #}\\\n    {% case \"a\" %}A{% /match %}";
+    let mut parser = TemplateParser::new(context, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("A", output_str);
+}
+
+#[test]
+fn parse_fn_call_1() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_forsplit_2/test.tenplate"),
+        concat!(
+            "{% set fnpath %}{% path \"./resources/parse_fn_call_1/functions/testobj.tenplate\" /%}{% /set %}",
+            "{% call fnpath /%}",
+            "{{ test.pagesize() }}\\",
+        ),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
 	assert_eq!(
-        "5+4+3+2+1+0",
+        "10",
         output_str
     );
 }
 
 #[test]
-fn parse_forsplit_3() {
+fn parse_fn_call_2() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_forsplit_3/test.tenplate"),
+		PathBuf::from("./resources/parse_fn_call_2/test.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
 	assert_eq!(
-        "iii",
+        "<h4 id=\"the_id\">Some text</h4>",
         output_str
     );
 }
 
 #[test]
-fn parse_forsplit_4() {
+fn parse_forrange_1() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_forsplit_4/test.tenplate"),
+		PathBuf::from("./resources/parse_forrange_1/test.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "No items.",
-        output_str
-    );
+	assert_eq!("1, 3, 5, 7, 9", output_str);
 }
 
 #[test]
-fn parse_forsplit_5() {
+fn parse_forrange_2() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_forsplit_5/test.tenplate"),
+		PathBuf::from("./resources/parse_forrange_2/test.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
 	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "No items.",
-        output_str
-    );
+	assert_eq!("4: 4-3-2-1", output_str);
 }
 
 #[test]
-#[should_panic]
-fn parse_assert_3() {
+fn parse_forrange_3() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_assert_3/test.tenplate"),
+		PathBuf::from("./resources/parse_forrange_3/test.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("No items.", output_str);
 }
 
 #[test]
-fn parse_assert_4() {
+fn parse_forrange_4() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_assert_4/test.tenplate"),
+		PathBuf::from("./resources/parse_forrange_4/test.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
-    drop(parser);
+	drop(parser);
 	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "Passed",
-        output_str
-    );
+	assert_eq!("-2 -1 0 ", output_str);
 }
 
 #[test]
-fn parse_assert_5() {
+#[should_panic]
+fn parse_forrange_5() {
 	let mut output = Vec::<u8>::new();
 	let mut parser = TemplateParser::new(
 		Context::default(),
-		PathBuf::from("./resources/parse_assert_5/test.tenplate"),
+		PathBuf::from("./resources/parse_forrange_5/test.tenplate"),
 		&mut output,
 	).unwrap();
 	parser.parse().unwrap();
-    drop(parser);
-	let output_str = String::from_utf8(output).unwrap();
-	assert_eq!(
-        "Passed",
-        output_str
-    );
+}
+
+#[test]
+fn parse_forrange_6() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forrange_6/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("-9223372036854775808/3 -4611686018427387904/2 0/1 4611686018427387904/0 \n9223372036854775807/9223372036854775807 9223372036854775806/9223372036854775807 \n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_forrange_7() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forrange_7/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_break_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_break_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1, 2, 3", output_str);
+}
+
+#[test]
+fn parse_break_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_break_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("a1 a2 b1 b2 ", output_str);
+}
+
+#[test]
+fn parse_continue_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_continue_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1 3 5 ", output_str);
+}
+
+#[test]
+fn parse_continue_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_continue_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("b c ", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_break_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_break_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_break_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_break_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("unset", output_str);
+}
+
+#[test]
+fn parse_break_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_break_5/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("n1 n3 \n", output_str);
+}
+
+#[test]
+fn parse_loop_context_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_loop_context_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1/2 a>b; 2/1 a<b>c; 3/0 b<c; ", output_str);
+}
+
+#[test]
+fn parse_loop_context_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_loop_context_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("0.0 0.1 1.0 1.1 ", output_str);
+}
+
+#[test]
+fn parse_loop_context_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_loop_context_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("ok", output_str);
+}
+
+#[test]
+fn parse_cycle_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_cycle_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<tr class=\"odd\">1</tr><tr class=\"even\">2</tr><tr class=\"odd\">3</tr>", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_cycle_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_cycle_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_forkeys_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forkeys_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1. author: Frankie\n2. title: Blog\n", output_str);
+}
+
+#[test]
+fn parse_forkeys_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forkeys_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("title author empty\n", output_str);
+}
+
+#[test]
+fn parse_forkeys_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forkeys_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("title: Journal\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_forkeys_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forkeys_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_forline_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forline_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1/4 [apples]\n2/4 []\n3/4 [# not a fruit]\n4/4 [pears]\n", output_str);
+}
+
+#[test]
+fn parse_forline_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forline_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("pears\napples\nempty\n", output_str);
+}
+
+#[test]
+fn parse_forline_3() {
+    let mut output = Vec::<u8>::new();
+    let mut ctx = Context::default();
+    ctx.add_variable("names", "./", "Matthew\nFrankie\r\nKarina");
+    let input = "{% forline name in names as l %}{{ name }}{% if l.islast %}.{% else %}, {% /if %}{% /forline %}";
+    let mut parser = TemplateParser::new(ctx, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("Matthew, Frankie, Karina.", &output_str);
+}
+
+#[test]
+fn parse_forline_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forline_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("-<a>b\na<b>d\nb<d>-\nd b # c a \n", output_str);
+}
+
+#[test]
+fn parse_set_value_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_set_value_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("Frankie 30 Tom\nset\n", output_str);
+}
+
+#[test]
+fn parse_fn_object_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_fn_object_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("Hello, Frankie of Chicago\n", output_str);
+}
+
+#[test]
+fn parse_fn_args_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_fn_args_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<div class=\"plain\">One</div>\n<div class=\"wide\">Two</div>\n<div class=\"wide\">Three a Other</div>\n<div class=\"narrow\">Other</div>\n<div class=\"bare\"></div>\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_fn_args_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_fn_args_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+#[should_panic]
+fn parse_fn_args_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_fn_args_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_invoke_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_invoke_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<div class=\"panel\"><h2>Greeting</h2><p>Hello, Frankie!</p></div>\n<div class=\"wide\"><h2>Outer</h2><div class=\"panel\"><h2>Inner</h2>Frankie</div></div>\nnone\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_invoke_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_invoke_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_invoke_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_invoke_3/page.html"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<div>T<p>hi &amp; bye</p></div>\n", output_str);
+}
+
+#[test]
+fn parse_return_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_return_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("5\nignored output\nnone\n", output_str);
+}
+
+#[test]
+fn parse_return_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_return_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("12 none\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_return_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_return_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_return_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_return_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("one many late early\n", output_str);
+}
+
+#[test]
+fn parse_load_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_load_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("Notes by Frankie\n1. Hello\n2. World (draft)\n#rust #web \n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_load_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_load_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_load_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_load_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("Team\nFrankie (author)\nKarina (editor)\n", output_str);
+}
+
+#[test]
+fn parse_front_matter_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_front_matter_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("First Post\nSecond Post (draft)\nuntitled\n", output_str);
+}
+
+#[test]
+fn parse_forfile_glob_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forfile_glob_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	let site = PathBuf::from("./resources/listing/site").canonicalize().unwrap();
+	assert_eq!(concat!(
+            "1 site/blog/post-1.md\n",
+            "2 site/index.md\n",
+            "site/index.md\n",
+            "site/about.txt\n",
+            "site/.hidden.md\n",
+            "site/assets\n",
+            "site/blog\n",
+            "site/blog/.secret\n",
+            "site/blog/drafts\n",
+        ), output_str.replace(site.to_str().unwrap(), "site"));
+}
+
+#[test]
+fn parse_forfile_sort_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forfile_sort_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	let versions = PathBuf::from("./resources/listing/versions").canonicalize().unwrap();
+	assert_eq!(
+        "v1.txt, v2.md, v9.TXT, v10.txt\nv9.TXT\n",
+        output_str.replace(&format!("{}/", versions.display()), ""),
+    );
+}
+
+#[test]
+fn parse_forfile_path_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forfile_path_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("page a\ndir one b\n\n", output_str);
+}
+
+#[test]
+fn parse_stat_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_stat_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("v2.md v2 md 1 0\nlisting is a directory\n", output_str);
+}
+
+#[test]
+fn parse_read_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_read_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("[\n  first line\nsecond line\n\n]\n[first line\nsecond line]\n[nothing here]\n  FIRST LINE;SECOND LINE;\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_read_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_read_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_pop_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_pop_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("second\nfirst\ngone\nstill gone\n", output_str);
+}
+
+#[test]
+fn parse_unset_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_unset_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("second\ngone\nnobody\nempty\n", output_str);
+}
+
+#[test]
+fn parse_with_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_with_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1 2 2\n3\n1\nouter none none\n", output_str);
+}
+
+#[test]
+fn parse_with_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_with_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1, 2, 3\nnone\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_with_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_with_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_with_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_with_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("[]\n3 0\n", output_str);
+}
+
+#[test]
+fn parse_with_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_with_5/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("inner outer\n", output_str);
+}
+
+#[test]
+fn parse_block_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_block_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<title>Post | Blog | Site</title>\n<main><p>Hello</p>no posts</main>\n(c) base\n[layout page]\n", output_str);
+}
+
+#[test]
+fn parse_block_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_block_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<li>1</li><li>2</li><li>3</li>\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_block_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_block_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_forsplit_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forsplit_1/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "0, 1, 2, 3, 4, 5",
+        output_str
+    );
+}
+
+#[test]
+fn parse_forsplit_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forsplit_2/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "5+4+3+2+1+0",
+        output_str
+    );
+}
+
+#[test]
+fn parse_forsplit_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forsplit_3/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "iii",
+        output_str
+    );
+}
+
+#[test]
+fn parse_fn_object_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_fn_object_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("[Alice]\nAlice Bob \nouter\n", output_str);
+}
+
+#[test]
+fn parse_forsplit_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forsplit_4/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "No items.",
+        output_str
+    );
+}
+
+#[test]
+fn parse_forsplit_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_forsplit_5/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "No items.",
+        output_str
+    );
+}
+
+#[test]
+#[should_panic]
+fn parse_assert_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_assert_3/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_assert_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_assert_4/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+    drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "Passed",
+        output_str
+    );
+}
+
+#[test]
+fn parse_front_matter_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_front_matter_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	let error = parser.parse().unwrap_err();
+	assert!(error.message().contains("posts/b.md"), "{}", error.message());
+}
+
+#[test]
+fn parse_front_matter_3() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_front_matter_3/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("[+++\ntitle = \"Included\"\n+++\nincluded body\ncompiled\nyes\n]\n", output_str);
+}
+
+#[test]
+fn parse_assert_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_assert_5/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+    drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!(
+        "Passed",
+        output_str
+    );
+}
+
+#[test]
+fn parse_typed_values_1() {
+    let mut output = Vec::<u8>::new();
+    let input = "{% if 2.5 < 10 && -1 < 0 %}Passed{% else %}Failed{% /if %}";
+    let mut parser = TemplateParser::new(Context::default(), input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("Passed", &output_str);
+}
+
+#[test]
+fn parse_typed_values_2() {
+    let mut output = Vec::<u8>::new();
+    let mut ctx = Context::default();
+    ctx.add_variable("count", "./", Value::from(3_i64));
+    let input = "{% if count == \"3\" %}{{ count }}{% /if %}";
+    let mut parser = TemplateParser::new(ctx, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("3", &output_str);
+}
+
+#[test]
+fn parse_query_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_query_1/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("1:test.user, 2:another.user\n", output_str);
+}
+
+#[test]
+fn parse_query_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_query_2/test.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("another.user, none\n", output_str);
+}
+
+#[test]
+fn parse_filter_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_filter_1/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("A Tale... costs 12.50. NOTHING\n", output_str);
+}
+
+#[test]
+#[should_panic]
+fn parse_filter_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_filter_2/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+}
+
+#[test]
+fn parse_filter_3() {
+    let mut output = Vec::<u8>::new();
+    let mut ctx = Context::default();
+    ctx.add_variable("dessert", "./", "crème brûlée");
+    let input = "{{ dessert | upper }}";
+    let mut parser = TemplateParser::new(ctx, input, &mut output).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert_eq!("CRÈME BRÛLÉE", &output_str);
+}
+
+#[test]
+fn parse_filter_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_filter_4/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("HELLO, WORLD\nHELLO\n", output_str);
+}
+
+#[test]
+fn parse_filter_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_filter_5/file.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("{\"address\":{\"city\":\"Chicago\"},\"name\":\"Frankie\"}\n", output_str);
+}
+
+#[test]
+fn parse_autoescape_1() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_autoescape_1/page.html"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("&lt;b&gt;&quot;Tom&quot; &amp; Jerry&lt;/b&gt;\n<b>\"Tom\" & Jerry</b>\n<b>\"Tom\" & Jerry</b>\n&lt;b&gt;&quot;Tom&quot; &amp; Jerry&lt;/b&gt;\n", output_str);
+}
+
+#[test]
+fn parse_autoescape_2() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_autoescape_2/page.tenplate"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<b>\n", output_str);
+}
+
+#[test]
+fn parse_autoescape_3() {
+    let mut output = Vec::<u8>::new();
+    let mut ctx = Context::default();
+    ctx.add_variable("x", "./", "<b>");
+    ctx.set_escape(Escape::None);
+    let mut parser = TemplateParser::new(
+        ctx,
+        PathBuf::from("./resources/parse_autoescape_1/page.html"),
+        &mut output,
+    ).unwrap();
+    parser.parse().unwrap();
+    drop(parser);
+
+    let output_str = String::from_utf8(output).unwrap();
+    assert!(output_str.starts_with("<b>\"Tom\" & Jerry</b>\n"));
+}
+
+#[test]
+fn parse_autoescape_4() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_autoescape_4/page.html"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("Tom &amp; Jerry\n&lt;i&gt;\n", output_str);
+}
+
+#[test]
+fn parse_autoescape_5() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_autoescape_5/page.html"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("<main><p>Tom &amp; Jerry</p></main>\n", output_str);
+}
+
+#[test]
+fn parse_autoescape_6() {
+	let mut output = Vec::<u8>::new();
+	let mut parser = TemplateParser::new(
+		Context::default(),
+		PathBuf::from("./resources/parse_autoescape_6/page.html"),
+		&mut output,
+	).unwrap();
+	parser.parse().unwrap();
+	drop(parser);
+	let output_str = String::from_utf8(output).unwrap();
+	assert_eq!("&lt;test.user\n", output_str);
 }
@@ -10,10 +10,12 @@ use {
             InternalResult,
             IntoInternal,
         },
+        filter::Filter,
     },
     std::{
         cmp::Ordering,
-        collections::HashMap,
+        collections::{ BTreeMap, HashMap, },
+        fmt::{ Display, Formatter, Result as FmtResult, },
     },
     rusqlite::{
         self,
@@ -32,7 +34,7 @@ use {
 };
 
 pub(crate) trait ToOutput {
-    fn to_output(&self, formatters: ()) -> InternalResult<String>;
+    fn to_output(&self, filters: &[Filter]) -> InternalResult<String>;
 }
 
 pub(crate) trait IsTruthy {
@@ -63,14 +65,14 @@ impl PartialEq<Real> for Integer {
 impl IsTruthy for Integer {
     fn is_truthy(&self) -> bool {
         match self.0 {
-            Some(a) => a > 0,
+            Some(a) => a != 0,
             None => false,
         }
     }
 }
 
 impl ToSql for Integer {
-    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput> {
+    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput<'_>> {
         Ok(types::ToSqlOutput::Borrowed(self.get().as_ref()
             .map_or(ValueRef::Null, |v| ValueRef::Integer(*v))
         ))
@@ -90,10 +92,9 @@ impl FromSql for Integer {
 }
 
 impl ToOutput for Integer {
-    fn to_output(&self, _: ()) -> InternalResult<String> {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
         match &self.0 {
             Some(t) => {
-                // TODO: Format
                 Ok(format!("{t}"))
             },
             None => {
@@ -133,14 +134,14 @@ impl PartialEq<Integer> for Real {
 impl IsTruthy for Real {
     fn is_truthy(&self) -> bool {
         match self.0 {
-            Some(a) => a > 0_f64,
+            Some(a) => a != 0_f64,
             None => false,
         }
     }
 }
 
 impl ToSql for Real {
-    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput> {
+    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput<'_>> {
         Ok(types::ToSqlOutput::Borrowed(self.get().as_ref()
             .map_or(ValueRef::Null, |v| ValueRef::Real(*v))
         ))
@@ -160,10 +161,9 @@ impl FromSql for Real {
 }
 
 impl ToOutput for Real {
-    fn to_output(&self, _: ()) -> InternalResult<String> {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
         match &self.0 {
             Some(t) => {
-                // TODO: Format
                 Ok(format!("{t}"))
             },
             None => {
@@ -185,14 +185,14 @@ impl Text {
 impl IsTruthy for Text {
     fn is_truthy(&self) -> bool {
         match &self.0 {
-            Some(t) => !t.is_empty(),
+            Some(t) => !matches!(t.to_lowercase().as_str(), "n"|"0"|""|"false"),
             None => false,
         }
     }
 }
 
 impl ToSql for Text {
-    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput> {
+    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput<'_>> {
         Ok(types::ToSqlOutput::Borrowed(self.get().as_ref()
             .map_or(ValueRef::Null, |v| ValueRef::Text(v.as_bytes()))
         ))
@@ -215,10 +215,9 @@ impl FromSql for Text {
 }
 
 impl ToOutput for Text {
-    fn to_output(&self, _: ()) -> InternalResult<String> {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
         match &self.0 {
             Some(t) => {
-                // TODO: Format
                 Ok(t.to_owned())
             },
             None => {
@@ -247,7 +246,7 @@ impl IsTruthy for Blob {
 }
 
 impl ToSql for Blob {
-    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput> {
+    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput<'_>> {
         Ok(types::ToSqlOutput::Borrowed(self.get().as_ref()
             .map_or(ValueRef::Null, |v| ValueRef::Blob(v.as_slice()))
         ))
@@ -267,10 +266,9 @@ impl FromSql for Blob {
 }
 
 impl ToOutput for Blob {
-    fn to_output(&self, _: ()) -> InternalResult<String> {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
         match &self.0 {
             Some(t) => {
-                // TODO: Format
                 Ok(BASE64_STANDARD.encode(t))
             },
             None => {
@@ -290,21 +288,22 @@ impl IsTruthy for Row {
 }
 
 impl ToOutput for Row {
-    fn to_output(&self, _: ()) -> InternalResult<String> {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
         Err(InternalError::new("Row cannot be output"))
     }
 }
 
+// column names are case-insensitive, so they are stored in uppercase
 impl Row {
     pub(crate) fn get(&self, name: &str) -> Option<&Value> {
-        self.0.get(name)
+        self.0.get(&name.to_uppercase())
     }
 
     pub(crate) fn insert<S>(&mut self, name: S, value: Value) -> InternalResult<Option<Value>>
     where
         S: AsRef<str>,
     {
-        let alias = Alias::from_str(name.as_ref())?;
+        let alias = name.as_ref().parse::<Alias>()?;
 
         if alias.has_row() && !alias.has_column() {
             return Err(InternalError::new("Cannot insert a value at a specific row"));
@@ -324,7 +323,7 @@ impl Row {
                 Err(InternalError::new("A row cannot contain a rows"))
             },
             _ => {
-                Ok(self.0.insert(alias.into_variable(), value))
+                Ok(self.0.insert(alias.into_variable().to_uppercase(), value))
             },
         }
     }
@@ -334,10 +333,6 @@ impl Row {
 pub struct Rows(Vec<Value>);
 
 impl Rows {
-    pub(crate) fn get_row(&self, row: usize) -> Option<&Value> {
-        self.0.get(row)
-    }
-
     pub(crate) fn get_column(&self, column: &str) -> Option<&Value> {
         self.0.first().and_then(|v| v.as_row().and_then(|c| c.get(column)))
     }
@@ -357,20 +352,90 @@ impl IsTruthy for Rows {
 }
 
 impl ToOutput for Rows {
-    fn to_output(&self, _: ()) -> InternalResult<String> {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
         Err(InternalError::new("Rows cannot be output"))
     }
 }
 
-#[derive(Clone, Debug)]
+/// A map of named values, i.e. an object loaded from data or assembled from
+/// the `name.key` variables in context.
+#[derive(Clone, Debug, Default)]
+pub struct Map(BTreeMap<String, Value>);
+
+impl Map {
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.0.get_mut(key)
+    }
+
+    pub(crate) fn insert<S: Into<String>, V: Into<Value>>(&mut self, key: S, value: V) -> Option<Value> {
+        self.0.insert(key.into(), value.into())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl IsTruthy for Map {
+    fn is_truthy(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+impl ToOutput for Map {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
+        Err(InternalError::new("Map cannot be output"))
+    }
+}
+
+/// An ordered list of values.
+#[derive(Clone, Debug, Default)]
+pub struct List(Vec<Value>);
+
+impl List {
+    pub(crate) fn get(&self, index: usize) -> Option<&Value> {
+        self.0.get(index)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl IsTruthy for List {
+    fn is_truthy(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+impl ToOutput for List {
+    fn to_output(&self, _: &[Filter]) -> InternalResult<String> {
+        Err(InternalError::new("List cannot be output"))
+    }
+}
+
+impl From<Vec<Value>> for List {
+    fn from(input: Vec<Value>) -> Self {
+        Self(input)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub enum Value {
     Integer(Integer),
     Real(Real),
     Text(Text),
     Blob(Blob),
+    #[default]
     Null,
     Row(Row),
     Rows(Rows),
+    Map(Map),
+    List(List),
 }
 
 impl IsTruthy for Value {
@@ -383,6 +448,8 @@ impl IsTruthy for Value {
             Value::Null => false,
             Value::Row(c) => c.is_truthy(),
             Value::Rows(m) => m.is_truthy(),
+            Value::Map(m) => m.is_truthy(),
+            Value::List(l) => l.is_truthy(),
         }
     }
 }
@@ -411,6 +478,8 @@ impl PartialEq for Value {
             Value::Null => false,
             Value::Row(_) => false,
             Value::Rows(_) => false,
+            Value::Map(_) => false,
+            Value::List(_) => false,
         }
     }
 }
@@ -437,12 +506,220 @@ impl PartialOrd for Value {
             Value::Null => None,
             Value::Row(_) => None,
             Value::Rows(_) => None,
+            Value::Map(_) => None,
+            Value::List(_) => None,
+        }
+    }
+}
+
+impl PartialEq<Value> for str {
+    fn eq(&self, other: &Value) -> bool {
+        match other {
+            Value::Text(Text(Some(t))) => self == t,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<Value> for &str {
+    fn eq(&self, other: &Value) -> bool {
+        (*self).eq(other)
+    }
+}
+
+// Conditions compare values loosely: text which looks like a number is
+// compared numerically against numbers, and two texts are only compared
+// numerically when both are integers.
+impl Value {
+    pub(crate) fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Text(Text(Some(t))), Value::Integer(Integer(Some(i))))|
+            (Value::Integer(Integer(Some(i))), Value::Text(Text(Some(t)))) => {
+                t.trim().parse::<i64>().is_ok_and(|t| t == *i)
+            },
+            (Value::Text(Text(Some(t))), Value::Real(Real(Some(r))))|
+            (Value::Real(Real(Some(r))), Value::Text(Text(Some(t)))) => {
+                t.trim().parse::<f64>().is_ok_and(|t| t == *r)
+            },
+            (Value::Null, Value::Null) => true,
+            (a, b) => a == b,
+        }
+    }
+
+    pub(crate) fn loose_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(Integer(Some(a))), Value::Integer(Integer(Some(b)))) => a.partial_cmp(b),
+            (Value::Integer(a), Value::Real(b)) => Real::from(*a).partial_cmp(b),
+            (Value::Real(a), Value::Integer(b)) => a.partial_cmp(&Real::from(*b)),
+            (Value::Real(a), Value::Real(b)) => a.partial_cmp(b),
+            (Value::Text(Text(Some(a))), Value::Text(Text(Some(b)))) => {
+                match (a.parse::<i64>(), b.parse::<i64>()) {
+                    (Ok(i), Ok(j)) => i.partial_cmp(&j),
+                    _ => a.partial_cmp(b),
+                }
+            },
+            (Value::Text(Text(Some(t))), Value::Integer(_)|Value::Real(_)) => {
+                match t.trim().parse::<f64>() {
+                    Ok(t) => Value::from(t).loose_cmp(other),
+                    Err(_) => Some(t.as_str().cmp(other.to_output(&[]).ok()?.as_str())),
+                }
+            },
+            (Value::Integer(_)|Value::Real(_), Value::Text(_)) => {
+                other.loose_cmp(self).map(Ordering::reverse)
+            },
+            (a, b) => a.partial_cmp(b),
+        }
+    }
+
+    pub(crate) fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Integer(Integer(Some(i))) => Some(Number::Integer(*i)),
+            Value::Real(Real(Some(r))) => Some(Number::Real(*r)),
+            Value::Text(Text(Some(t))) => Number::parse(t),
+            _ => None,
+        }
+    }
+}
+
+/// A number used in arithmetic. Integers stay integers unless the other side
+/// of an operation is real, and overflow is reported rather than wrapped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Number {
+    Integer(i64),
+    Real(f64),
+}
+
+impl Number {
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+
+        if let Ok(i) = input.parse::<i64>() {
+            return Some(Self::Integer(i));
+        }
+
+        input.parse::<f64>().ok()
+            .filter(|r| r.is_finite())
+            .map(Self::Real)
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::Integer(i) => i as f64,
+            Self::Real(r) => r,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        match self {
+            Self::Integer(i) => i == 0,
+            Self::Real(r) => r == 0.0,
+        }
+    }
+
+    fn apply<I, R>(self, other: Self, operator: &str, int_op: I, real_op: R) -> InternalResult<Self>
+    where
+        I: Fn(i64, i64) -> Option<i64>,
+        R: Fn(f64, f64) -> f64,
+    {
+        let expression = format!("'{} {operator} {}'", self.brief(), other.brief());
+
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => int_op(a, b)
+                .map(Self::Integer)
+                .into_internal(format!("{expression} overflowed")),
+            (a, b) => match real_op(a.as_f64(), b.as_f64()) {
+                r if r.is_nan() => Err(InternalError::new(format!(
+                    "{expression} is not a number"
+                ))),
+                r if r.is_infinite() => Err(InternalError::new(format!(
+                    "{expression} overflowed"
+                ))),
+                r => Ok(Self::Real(r)),
+            },
+        }
+    }
+
+    /// Formats the number for an error message, using scientific notation for
+    /// reals too large to print in full.
+    fn brief(self) -> String {
+        match self {
+            Self::Real(r) if r.abs() >= 1e16 => format!("{r:e}"),
+            n => n.to_string(),
+        }
+    }
+
+    pub(crate) fn add(self, other: Self) -> InternalResult<Self> {
+        self.apply(other, "+", i64::checked_add, |a, b| a + b)
+    }
+
+    pub(crate) fn sub(self, other: Self) -> InternalResult<Self> {
+        self.apply(other, "-", i64::checked_sub, |a, b| a - b)
+    }
+
+    pub(crate) fn mul(self, other: Self) -> InternalResult<Self> {
+        self.apply(other, "*", i64::checked_mul, |a, b| a * b)
+    }
+
+    pub(crate) fn div(self, other: Self) -> InternalResult<Self> {
+        if other.is_zero() {
+            return Err(InternalError::new(format!("Cannot divide {} by zero", self.brief())));
+        }
+
+        self.apply(other, "/", i64::checked_div, |a, b| a / b)
+    }
+
+    pub(crate) fn rem(self, other: Self) -> InternalResult<Self> {
+        if other.is_zero() {
+            return Err(InternalError::new(format!(
+                "Cannot take the remainder of {} divided by zero", self.brief()
+            )));
+        }
+
+        self.apply(other, "%", i64::checked_rem, |a, b| a % b)
+    }
+
+    pub(crate) fn pow(self, other: Self) -> InternalResult<Self> {
+        match (self, other) {
+            // zero to a negative power is a division by zero
+            (_, _) if self.is_zero() && other.as_f64() < 0.0 => {
+                Err(InternalError::new(format!(
+                    "Cannot raise zero to the negative power {}", other.brief()
+                )))
+            },
+            // a negative exponent produces a fraction
+            (Self::Integer(_), Self::Integer(b)) if b < 0 => {
+                Self::Real(self.as_f64()).pow(other)
+            },
+            _ => self.apply(
+                other,
+                "^",
+                |a, b| u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+                f64::powf,
+            ),
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Real(r) => write!(f, "{r}"),
+        }
+    }
+}
+
+impl From<Number> for Value {
+    fn from(input: Number) -> Self {
+        match input {
+            Number::Integer(i) => Value::from(i),
+            Number::Real(r) => Value::from(r),
         }
     }
 }
 
 impl ToSql for Value {
-    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput> {
+    fn to_sql(&self) -> rusqlite::Result<types::ToSqlOutput<'_>> {
         match self {
             Self::Integer(i) => i.to_sql(),
             Self::Real(r) => r.to_sql(),
@@ -451,20 +728,28 @@ impl ToSql for Value {
             Self::Null => Null.to_sql(),
             Self::Row(_) => Err(rusqlite::Error::InvalidQuery),
             Self::Rows(_) => Err(rusqlite::Error::InvalidQuery),
+            Self::Map(_) => Err(rusqlite::Error::InvalidQuery),
+            Self::List(_) => Err(rusqlite::Error::InvalidQuery),
         }
     }
 }
 
 impl ToOutput for Value {
-    fn to_output(&self, formatters: ()) -> InternalResult<String> {
+    fn to_output(&self, filters: &[Filter]) -> InternalResult<String> {
+        if let Some((filter, filters)) = filters.split_first() {
+            return filter.apply(self)?.to_output(filters);
+        }
+
         match self {
-            Self::Integer(i) => i.to_output(formatters),
-            Self::Real(r) => r.to_output(formatters),
-            Self::Text(t) => t.to_output(formatters),
-            Self::Blob(b) => b.to_output(formatters),
+            Self::Integer(i) => i.to_output(filters),
+            Self::Real(r) => r.to_output(filters),
+            Self::Text(t) => t.to_output(filters),
+            Self::Blob(b) => b.to_output(filters),
             Self::Null => Ok(String::new()),
-            Self::Row(c) => c.to_output(formatters),
-            Self::Rows(m) => m.to_output(formatters),
+            Self::Row(c) => c.to_output(filters),
+            Self::Rows(m) => m.to_output(filters),
+            Self::Map(m) => m.to_output(filters),
+            Self::List(l) => l.to_output(filters),
         }
     }
 }
@@ -499,22 +784,19 @@ impl From<Row> for Value {
     }
 }
 
-impl Value {
-    #[cfg(test)]
-    pub(crate) fn as_integer(&self) -> Option<&Integer> {
-        match self {
-            Self::Integer(i) => Some(i),
-            _ => None,
-        }
+impl From<Map> for Value {
+    fn from(input: Map) -> Self {
+        Self::Map(input)
     }
+}
 
-    pub(crate) fn as_text(&self) -> Option<&Text> {
-        match self {
-            Self::Text(t) => Some(t),
-            _ => None,
-        }
+impl From<List> for Value {
+    fn from(input: List) -> Self {
+        Self::List(input)
     }
+}
 
+impl Value {
     pub(crate) fn as_row(&self) -> Option<&Row> {
         match self {
             Self::Row(c) => Some(c),
@@ -522,13 +804,6 @@ impl Value {
         }
     }
 
-    pub(crate) fn as_rows(&self) -> Option<&Rows> {
-        match self {
-            Self::Rows(m) => Some(m),
-            _ => None,
-        }
-    }
-
     pub(crate) fn get_column(&self, column: &str) -> Option<&Value> {
         match self {
             Self::Rows(m) => m.get_column(column),
@@ -537,39 +812,47 @@ impl Value {
         }
     }
 
-    pub(crate) fn into_vec(self) -> Vec<Value> {
+    /// A member of this value, i.e. the key of a map, the index of a list or
+    /// rows, or the column of a row.
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
         match self {
-            Self::Rows(m) => m.0,
-            _ => vec![ self ],
+            Self::Map(m) => m.get(key),
+            Self::List(l) => l.get(key.parse::<usize>().ok()?),
+            Self::Rows(m) => match key.parse::<usize>() {
+                Ok(index) => m.0.get(index),
+                Err(_) => self.get_column(key),
+            },
+            _ => self.get_column(key),
         }
     }
 
-    #[cfg(test)]
-    pub(crate) fn iter(&self) -> ValueIter<'_> {
-        ValueIter::from(self)
-    }
-}
-
-pub struct ValueIter<'value> {
-    values: std::vec::IntoIter<&'value Value>,
-}
-
-impl<'value> From<&'value Value> for ValueIter<'value> {
-    fn from(value: &'value Value) -> Self {
-        match value {
-            Value::Rows(m) => Self {
-                values: m.0.iter().collect::<Vec<&Value>>().into_iter(),
+    /// The key and value of each member of this value, in order.
+    pub(crate) fn entries(&self) -> Vec<(Value, Value)> {
+        match self {
+            Self::Map(m) => m.0.iter()
+                .map(|(k, v)| (Value::from(k), v.to_owned()))
+                .collect(),
+            Self::List(List(values))|Self::Rows(Rows(values)) => values.iter()
+                .enumerate()
+                .map(|(i, v)| (Value::from(i as i64), v.to_owned()))
+                .collect(),
+            Self::Row(c) => {
+                let mut columns = c.0.iter()
+                    .map(|(k, v)| (Value::from(k), v.to_owned()))
+                    .collect::<Vec<(Value, Value)>>();
+                columns.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                columns
             },
-            _ => Self { values: vec![ value ].into_iter(), },
+            _ => Vec::new(),
         }
     }
-}
-
-impl<'value> Iterator for ValueIter<'value> {
-    type Item = &'value Value;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.values.next()
+    pub(crate) fn into_vec(self) -> Vec<Value> {
+        match self {
+            Self::Rows(m) => m.0,
+            Self::List(l) => l.0,
+            _ => vec![ self ],
+        }
     }
 }
 
@@ -609,6 +892,12 @@ impl From<String> for Value {
     }
 }
 
+impl<'from> From<&'from String> for Value {
+    fn from(input: &'from String) -> Self {
+        input.as_str().into()
+    }
+}
+
 impl<'from> From<Option<&'from str>> for Value {
     fn from(input: Option<&'from str>) -> Self {
         input.map(str::to_owned).into()
@@ -653,10 +942,10 @@ impl Value {
 }
 
 impl ToOutput for Option<&Value> {
-    fn to_output(&self, formatters: ()) -> InternalResult<String> {
+    fn to_output(&self, filters: &[Filter]) -> InternalResult<String> {
         match self {
-            Some(v) => v.to_output(formatters),
-            None => Ok(String::new()),
+            Some(v) => v.to_output(filters),
+            None => Value::Null.to_output(filters),
         }
     }
 }
@@ -1,9 +1,13 @@
+// these tests exercise `Value == Value`, not the `&str` shortcuts
+#![allow(clippy::cmp_owned)]
+
 use {
     crate::{
         context::{ Alias, Context },
-        value::Value,
+        value::{ List, Map, Number, Value, },
     },
     rusqlite::Connection,
+    std::str::FromStr,
 };
 
 fn db() -> Connection {
@@ -77,6 +81,20 @@ fn query_w_param() {
     assert_eq!(&Value::from("Shrek".to_owned()), value);
 }
 
+#[test]
+fn query_w_dotted_param() {
+    let conn = db();
+    let mut ctx = Context::default();
+    ctx.add_variable("params.id", "", 1_i64);
+    let mut stmt = conn.prepare("select nickname from person where id = $params::id").unwrap();
+    let result = ctx.query(&mut stmt).unwrap();
+    let value_opt = result.get_column("NICKNAME");
+    assert!(value_opt.is_some());
+
+    let value = value_opt.unwrap();
+    assert_eq!(&Value::from("Shrek".to_owned()), value);
+}
+
 #[test]
 fn integer_equality_1() {
     assert!(Value::from(1_i64) == Value::from(1_i64));
@@ -156,3 +174,74 @@ fn blob_equality_3() {
 fn blob_equality_4() {
     assert!(!(Value::from(vec![ 0x00, 0x01, 0x02 ]) != Value::from(vec![ 0x00, 0x01, 0x02 ])));
 }
+
+#[test]
+fn number_integer_preserved_1() {
+    let result = Number::Integer(7).div(Number::Integer(2)).unwrap();
+    assert_eq!(Number::Integer(3), result);
+}
+
+#[test]
+fn number_real_promotion_1() {
+    let result = Number::Integer(7).div(Number::Real(2.0)).unwrap();
+    assert_eq!(Number::Real(3.5), result);
+}
+
+#[test]
+fn number_overflow_1() {
+    assert!(Number::Integer(i64::MAX).add(Number::Integer(1)).is_err());
+}
+
+#[test]
+fn number_overflow_2() {
+    let error = Number::Real(1e308).mul(Number::Integer(10)).unwrap_err();
+    assert_eq!("'1e308 * 10' overflowed", error.message());
+}
+
+#[test]
+fn number_zero_power_1() {
+    let error = Number::Integer(0).pow(Number::Integer(-1)).unwrap_err();
+    assert_eq!("Cannot raise zero to the negative power -1", error.message());
+}
+
+#[test]
+fn number_not_a_number_1() {
+    let error = Number::Integer(-8).pow(Number::Real(0.5)).unwrap_err();
+    assert_eq!("'-8 ^ 0.5' is not a number", error.message());
+}
+
+#[test]
+fn number_parse_1() {
+    assert_eq!(Some(Number::Real(0.5)), Number::parse(" 0.5\n"));
+    assert_eq!(None, Number::parse("NaN"));
+}
+
+#[test]
+fn map_get_1() {
+    let mut map = Map::default();
+    map.insert("name", "Frankie");
+    let value = Value::from(map);
+
+    assert_eq!("Frankie", value.get("name").unwrap());
+    assert!(value.get("age").is_none());
+}
+
+#[test]
+fn list_get_1() {
+    let value = Value::from(List::from(vec![ Value::from("a"), Value::from("b"), ]));
+
+    assert_eq!("b", value.get("1").unwrap());
+    assert!(value.get("2").is_none());
+}
+
+#[test]
+fn entries_1() {
+    let mut map = Map::default();
+    map.insert("b", 2_i64);
+    map.insert("a", 1_i64);
+
+    let entries = Value::from(map).entries();
+    assert_eq!(2, entries.len());
+    assert_eq!("a", entries[0].0);
+    assert!(Value::from(1_i64) == entries[0].1);
+}
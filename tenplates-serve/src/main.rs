@@ -16,7 +16,7 @@ use {
         sync::OnceLock,
     },
     tempfile::TempDir,
-    tenplates_core::{ Context, Tenplates },
+    tenplates_core::{ Context, Escape, Tenplates },
     tokio::net::TcpListener,
 };
 
@@ -71,7 +71,7 @@ fn base26(mut dec: usize) -> String {
 
     loop {
         let rem = dec % LETTERS.len();
-        dec = dec / LETTERS.len();
+        dec /= LETTERS.len();
 
         out = format!("{}{out}", LETTERS[rem]);
 
@@ -102,8 +102,7 @@ async fn main() {
     let mut variables = Vec::new();
 
     while let Some(long_arg) = long_args.next() {
-        if long_arg.starts_with("--") {
-            let long_arg = &long_arg[2..];
+        if let Some(long_arg) = long_arg.strip_prefix("--") {
 
             match long_arg {
                 "get" => gets.push(from_dkv(long_args.next().unwrap())),
@@ -122,8 +121,8 @@ async fn main() {
                 unknown => panic!("Unknown argument --{unknown}"),
             }
         }
-        else if long_arg.starts_with('-') {
-            let mut short_args = long_arg[1..].chars();
+        else if let Some(short_args) = long_arg.strip_prefix('-') {
+            let mut short_args = short_args.chars();
 
             while let Some(short_arg) = short_args.next() {
                 match short_arg {
@@ -178,12 +177,15 @@ async fn main() {
         app = app.route(route, get(async |query: Query<BTreeMap<String, String>>| -> (StatusCode, HeaderMap, Vec<u8>) {
             let mut output = Vec::<u8>::new();
 
+            // templates are always served as html
             let mut context = Context::default();
+            context.set_escape(Escape::Html);
+
             for (key, value) in query.iter() {
                 context.add_variable(format!("params.{key}"), "", value);
             }
 
-            for (key, value) in VARS.get_or_init(|| Vec::new()).iter() {
+            for (key, value) in VARS.get_or_init(Vec::new).iter() {
                 context.add_variable(key, "", value);
             }
 
@@ -17,6 +17,7 @@
 use {
     tenplates_core::{
         Context,
+        Escape,
         Tenplates,
     },
     std::{ io, path::PathBuf, },
@@ -69,6 +70,24 @@ fn set<P: AsRef<std::path::Path>>(ctx: &mut Context, pwd: P, dkv: String) -> boo
     true
 }
 
+fn escape(ctx: &mut Context, mode: Option<String>, arg: &str) {
+    let mode = match mode {
+        Some(mode) => mode,
+        None => {
+            eprintln!("tenplates: {arg} requires a value");
+            std::process::exit(1);
+        },
+    };
+
+    match mode.parse::<Escape>() {
+        Ok(escape) => ctx.set_escape(escape),
+        Err(_) => {
+            eprintln!("tenplates: invalid <MODE> passed to {arg}");
+            std::process::exit(1);
+        },
+    }
+}
+
 fn version() -> ! {
     println!("tenplates: v{}", get_short_version());
 	std::process::exit(0)
@@ -88,6 +107,7 @@ fn main() {
     while let Some(full_arg) = args.next() {
         if let Some(long_arg) = full_arg.strip_prefix("--") {
             match long_arg {
+                "escape" => escape(&mut ctx, args.next(), "--escape"),
                 "help" => help(),
                 "set" => {
                     let arg = match args.next() {
@@ -113,6 +133,14 @@ fn main() {
         else if full_arg.starts_with('-') && full_arg.len() > 1 {
             let mut short_args = full_arg[1..].chars();
             match short_args.next() {
+                Some('e') => {
+                    if short_args.next().is_some() {
+                        eprintln!("tenplates: -e requires a value");
+                        std::process::exit(1);
+                    }
+
+                    escape(&mut ctx, args.next(), "-e");
+                },
                 Some('h') => help(),
                 Some('s') => {
                     if short_args.next().is_some() {